@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use std::io::Error;
+use std::path::Path;
+use std::process::Command;
+
+use crate::ref_selection::RefSelection;
+use crate::sizer_report::{format_bytes, SizerReport};
+
+/// The refspec which fetches every pull request's head and merge refs
+pub const PULL_REQUEST_REFSPEC: &str = "+refs/pull/*:refs/pull/*";
+
+/// Checks a refspec to fetch into a bare clone, for use as a command line argument. The refspec
+/// must store what it fetches outside of `refs/heads` and `refs/tags`, so it can be sized
+/// separately from the branches and tags which are cloned by default.
+pub fn parse_refspec(refspec: &str) -> Result<String, String> {
+    let Some((source, destination)) = refspec.trim_start_matches('+').split_once(':') else {
+        return Err(format!(
+            "`{}` isn't a refspec with a source and a destination, e.g. `{}`",
+            refspec, PULL_REQUEST_REFSPEC
+        ));
+    };
+
+    if source.is_empty() || !destination.starts_with("refs/") {
+        return Err(format!(
+            "`{}` isn't a refspec with a source and a destination, e.g. `{}`",
+            refspec, PULL_REQUEST_REFSPEC
+        ));
+    }
+
+    if ["refs/heads", "refs/tags"].contains(&destination_prefix(refspec).as_str())
+        || destination.starts_with("refs/heads/")
+        || destination.starts_with("refs/tags/")
+    {
+        return Err(format!(
+            "`{}` fetches into refs/heads or refs/tags, so its size couldn't be reported separately",
+            refspec
+        ));
+    }
+
+    Ok(refspec.to_string())
+}
+
+/// The prefix of the refs a refspec fetches into, e.g. `refs/pull` for `+refs/pull/*:refs/pull/*`
+pub fn destination_prefix(refspec: &str) -> String {
+    let destination = refspec
+        .split_once(':')
+        .map_or(refspec, |(_, destination)| destination);
+    let destination = destination.split('*').next().unwrap_or(destination);
+
+    destination.trim_end_matches('/').to_string()
+}
+
+/// Fetches extra refs into a bare clone. If the clone is shallow, only history since the same
+/// date is fetched.
+pub fn fetch(path: &Path, refspecs: &[String], since: Option<&str>) -> Result<(), Error> {
+    eprintln!("Fetching {}...", refspecs.join(", "));
+
+    // As in the clone cache, `gh` is used as the credential helper, as it is for `gh repo
+    // clone`, so private repositories can be fetched
+    let mut fetch_command = Command::new("git");
+    fetch_command
+        .arg("-C")
+        .arg(path)
+        .arg("-c")
+        .arg("credential.helper=")
+        .arg("-c")
+        .arg("credential.helper=!gh auth git-credential")
+        .arg("fetch")
+        .arg("--quiet");
+
+    if let Some(since) = since {
+        fetch_command.arg(format!("--shallow-since={}", since));
+    }
+
+    let fetch_output = fetch_command.arg("origin").args(refspecs).output()?;
+
+    if !fetch_output.status.success() {
+        return Err(Error::other(
+            String::from_utf8_lossy(&fetch_output.stderr).to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Counts the refs fetched by a set of refspecs
+pub fn count_refs(path: &Path, refspecs: &[String]) -> Result<u64, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("for-each-ref")
+        .arg("--format=%(refname)")
+        .args(refspecs.iter().map(|refspec| destination_prefix(refspec)))
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::other(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).lines().count() as u64)
+}
+
+/// Extends a selection of refs to include the refs fetched by a set of refspecs. A selection
+/// which only includes some refs, e.g. `--refs refs/heads/main`, would otherwise leave out the
+/// fetched refs, so their size couldn't be compared.
+pub fn with_additional_refs(ref_selection: &RefSelection, refspecs: &[String]) -> RefSelection {
+    let mut ref_selection = ref_selection.clone();
+
+    if !ref_selection.refs.is_empty() {
+        ref_selection
+            .refs
+            .extend(refspecs.iter().map(|refspec| destination_prefix(refspec)));
+    }
+
+    ref_selection
+}
+
+/// The arguments which make `git-sizer` skip the refs fetched by a set of refspecs
+pub fn exclude_arguments(refspecs: &[String]) -> Vec<String> {
+    refspecs
+        .iter()
+        .map(|refspec| format!("--exclude={}", destination_prefix(refspec)))
+        .collect()
+}
+
+/// The main metrics which make up a repository's size
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RefsSize {
+    pub unique_commit_count: u64,
+    pub unique_tree_size: u64,
+    pub unique_blob_count: u64,
+    pub unique_blob_size: u64,
+}
+
+impl RefsSize {
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} commits, {} of trees and {} blobs totalling {}",
+            self.unique_commit_count,
+            format_bytes(self.unique_tree_size),
+            self.unique_blob_count,
+            format_bytes(self.unique_blob_size)
+        )
+    }
+}
+
+/// How much refs fetched in addition to the default branches and tags, e.g. pull request refs,
+/// add to a repository's size
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdditionalRefs {
+    pub refspecs: Vec<String>,
+    pub ref_count: u64,
+    /// The size of the repository without the additional refs
+    pub default_refs: RefsSize,
+    /// The size which is only reachable from the additional refs
+    pub additional_refs: RefsSize,
+}
+
+impl AdditionalRefs {
+    /// Compares `git-sizer`'s reports with and without the additional refs
+    pub fn compare(
+        refspecs: &[String],
+        ref_count: u64,
+        with_additional_refs: &SizerReport,
+        default_refs: &SizerReport,
+    ) -> AdditionalRefs {
+        AdditionalRefs {
+            refspecs: refspecs.to_vec(),
+            ref_count,
+            default_refs: RefsSize {
+                unique_commit_count: default_refs.unique_commit_count,
+                unique_tree_size: default_refs.unique_tree_size,
+                unique_blob_count: default_refs.unique_blob_count,
+                unique_blob_size: default_refs.unique_blob_size,
+            },
+            additional_refs: RefsSize {
+                unique_commit_count: with_additional_refs
+                    .unique_commit_count
+                    .saturating_sub(default_refs.unique_commit_count),
+                unique_tree_size: with_additional_refs
+                    .unique_tree_size
+                    .saturating_sub(default_refs.unique_tree_size),
+                unique_blob_count: with_additional_refs
+                    .unique_blob_count
+                    .saturating_sub(default_refs.unique_blob_count),
+                unique_blob_size: with_additional_refs
+                    .unique_blob_size
+                    .saturating_sub(default_refs.unique_blob_size),
+            },
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "* Default refs: {}\n* {} refs fetched with {}: {} more\n",
+            self.default_refs.to_text(),
+            self.ref_count,
+            self.refspecs.join(", "),
+            self.additional_refs.to_text()
+        )
+    }
+}
+
+#[test]
+fn parse_refspec_requires_a_separate_destination() {
+    assert_eq!(
+        parse_refspec(PULL_REQUEST_REFSPEC),
+        Ok(PULL_REQUEST_REFSPEC.to_string())
+    );
+    assert!(parse_refspec("refs/changes/*:refs/changes/*").is_ok());
+    assert!(parse_refspec("refs/pull/*").is_err());
+    assert!(parse_refspec("+refs/pull/*:refs/heads/pull/*").is_err());
+    assert!(parse_refspec("+refs/heads/*:refs/heads/*").is_err());
+}
+
+#[test]
+fn destination_prefix_stops_at_the_first_wildcard() {
+    assert_eq!(destination_prefix(PULL_REQUEST_REFSPEC), "refs/pull");
+    assert_eq!(
+        destination_prefix("refs/changes/*/head:refs/changes/*/head"),
+        "refs/changes"
+    );
+    assert_eq!(
+        destination_prefix("refs/meta/config:refs/meta/config"),
+        "refs/meta/config"
+    );
+}
+
+#[test]
+fn with_additional_refs_includes_fetched_refs_in_a_selection_of_refs() {
+    let refspecs = [PULL_REQUEST_REFSPEC.to_string()];
+    let selection = RefSelection {
+        refs: vec!["refs/heads/main".to_string()],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        with_additional_refs(&selection, &refspecs).refs,
+        vec!["refs/heads/main", "refs/pull"]
+    );
+    assert!(with_additional_refs(&selection, &refspecs).matches("refs/pull/1/head"));
+    assert_eq!(
+        with_additional_refs(&RefSelection::default(), &refspecs),
+        RefSelection::default()
+    );
+}
+
+#[test]
+fn compare_reports_what_additional_refs_add() {
+    let default_refs = SizerReport {
+        unique_commit_count: 10,
+        unique_blob_count: 20,
+        unique_blob_size: 1024,
+        ..Default::default()
+    };
+    let with_additional_refs = SizerReport {
+        unique_commit_count: 15,
+        unique_blob_count: 22,
+        unique_blob_size: 3072,
+        ..Default::default()
+    };
+
+    let additional_refs = AdditionalRefs::compare(
+        &[PULL_REQUEST_REFSPEC.to_string()],
+        4,
+        &with_additional_refs,
+        &default_refs,
+    );
+
+    assert_eq!(
+        additional_refs.to_text(),
+        "* Default refs: 10 commits, 0 B of trees and 20 blobs totalling 1.00 KiB\n\
+         * 4 refs fetched with +refs/pull/*:refs/pull/*: 5 commits, 0 B of trees and 2 blobs totalling 2.00 KiB more\n"
+    );
+}
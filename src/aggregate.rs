@@ -0,0 +1,410 @@
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use std::fs;
+use std::io::{Error, Write};
+
+use crate::enums::AggregateFormat;
+use crate::generate_script::parse_output_filename;
+use crate::report::Report;
+use crate::sizer_report::{format_value, to_json_string, Metric};
+use crate::wiki::is_wiki;
+
+// The metrics included as columns in the aggregated report, with their Markdown headings
+const KEY_METRICS: [(Metric, &str); 9] = [
+    (Metric::UniqueCommitCount, "Commits"),
+    (Metric::UniqueTreeCount, "Trees"),
+    (Metric::UniqueBlobCount, "Blobs"),
+    (Metric::UniqueBlobSize, "Total blob size"),
+    (Metric::MaxBlobSize, "Largest blob"),
+    (Metric::MaxTreeEntries, "Largest tree"),
+    (Metric::ReferenceCount, "References"),
+    (Metric::MaxHistoryDepth, "History depth"),
+    (Metric::MaxPathDepth, "Path depth"),
+];
+
+/// A result file which was successfully parsed
+pub struct Row {
+    pub repository: String,
+    pub report: Report,
+}
+
+/// A result file which matched the filename template, but couldn't be parsed
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Failure {
+    pub file: String,
+    pub reason: String,
+}
+
+impl Row {
+    fn level_of_concern(&self) -> u64 {
+        self.report.sizer_report.max_level_of_concern() as u64
+    }
+
+    fn migration_status(&self) -> Option<String> {
+        self.report
+            .migration_readiness
+            .as_ref()
+            .map(|readiness| readiness.status.to_string())
+    }
+}
+
+impl Serialize for Row {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        map.serialize_entry("repository", &self.repository)?;
+
+        for (metric, _) in KEY_METRICS.iter() {
+            map.serialize_entry(metric.key(), &self.report.sizer_report.value(*metric))?;
+        }
+
+        map.serialize_entry("level_of_concern", &self.level_of_concern())?;
+
+        if let Some(migration_status) = self.migration_status() {
+            map.serialize_entry("migration_status", &migration_status)?;
+        }
+
+        map.end()
+    }
+}
+
+#[derive(Serialize)]
+struct Aggregate<'a> {
+    repositories: &'a [Row],
+    failures: &'a [Failure],
+}
+
+/// Reads the result files in a directory which match a filename template, and returns them
+/// sorted by a metric, largest first, along with any files which couldn't be parsed
+pub fn read_directory(
+    directory: &str,
+    output_filename_template: &str,
+    sort_by: Metric,
+) -> Result<(Vec<Row>, Vec<Failure>), Error> {
+    let mut rows = Vec::new();
+    let mut failures = Vec::new();
+
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(directory)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+
+        let filename = entry.file_name().to_string_lossy().to_string();
+
+        let Some((owner, repository_name)) =
+            parse_output_filename(output_filename_template, &filename)
+        else {
+            continue;
+        };
+
+        // Wikis are sized into their own files by generated scripts, e.g. `gh-sizer.wiki.txt`,
+        // and aren't repositories in their own right, so they aren't aggregated
+        if is_wiki(&repository_name) {
+            continue;
+        }
+
+        let repository = match owner {
+            Some(owner) => format!("{}/{}", owner, repository_name),
+            None => repository_name,
+        };
+
+        match fs::read_to_string(entry.path()).and_then(|contents| Report::parse(&contents)) {
+            Ok(report) => rows.push(Row { repository, report }),
+            Err(e) => failures.push(Failure {
+                file: filename,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    rows.sort_by(|a, b| {
+        b.report
+            .sizer_report
+            .value(sort_by)
+            .cmp(&a.report.sizer_report.value(sort_by))
+            .then_with(|| a.repository.cmp(&b.repository))
+    });
+
+    Ok((rows, failures))
+}
+
+pub fn call(
+    directory: &str,
+    output_filename_template: &str,
+    sort_by: Metric,
+    format: AggregateFormat,
+    stderr: &mut impl Write,
+) -> Result<String, Error> {
+    let (rows, failures) = read_directory(directory, output_filename_template, sort_by)?;
+
+    writeln!(
+        stderr,
+        "Aggregating results for {} repositories from {} ({} files could not be read)",
+        rows.len(),
+        directory,
+        failures.len()
+    )?;
+
+    let output = match format {
+        AggregateFormat::Csv => {
+            for failure in &failures {
+                writeln!(
+                    stderr,
+                    "Failed to read {}: {}",
+                    failure.file, failure.reason
+                )?;
+            }
+
+            to_csv(&rows)
+        }
+        AggregateFormat::Json => to_json_string(&Aggregate {
+            repositories: &rows,
+            failures: &failures,
+        }),
+        AggregateFormat::Markdown => to_markdown(&rows, &failures),
+    };
+
+    Ok(output)
+}
+
+fn includes_migration_status(rows: &[Row]) -> bool {
+    rows.iter().any(|row| row.migration_status().is_some())
+}
+
+fn to_csv(rows: &[Row]) -> String {
+    let mut headings = vec!["repository".to_string()];
+    headings.extend(
+        KEY_METRICS
+            .iter()
+            .map(|(metric, _)| metric.key().to_string()),
+    );
+    headings.push("level_of_concern".to_string());
+
+    if includes_migration_status(rows) {
+        headings.push("migration_status".to_string());
+    }
+
+    let mut output = headings.join(",");
+    output.push('\n');
+
+    for row in rows {
+        let mut fields = vec![csv_field(&row.repository)];
+        fields.extend(
+            KEY_METRICS
+                .iter()
+                .map(|(metric, _)| row.report.sizer_report.value(*metric).to_string()),
+        );
+        fields.push(row.level_of_concern().to_string());
+
+        if includes_migration_status(rows) {
+            fields.push(csv_field(&row.migration_status().unwrap_or_default()));
+        }
+
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+
+    output
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_markdown(rows: &[Row], failures: &[Failure]) -> String {
+    let mut headings = vec!["Repository"];
+    headings.extend(KEY_METRICS.iter().map(|(_, heading)| *heading));
+    headings.push("Level of concern");
+
+    if includes_migration_status(rows) {
+        headings.push("Migration status");
+    }
+
+    let mut output = format!("| {} |\n", headings.join(" | "));
+    output.push_str(&format!(
+        "|{}\n",
+        headings.iter().map(|_| " --- |").collect::<String>()
+    ));
+
+    for row in rows {
+        let mut cells = vec![row.repository.clone()];
+        cells.extend(KEY_METRICS.iter().map(|(metric, _)| {
+            let (numeral, unit) =
+                format_value(row.report.sizer_report.value(*metric), metric.unit());
+            format!("{} {}", numeral, unit).trim_end().to_string()
+        }));
+        cells.push("*".repeat(row.level_of_concern().min(30) as usize));
+
+        if includes_migration_status(rows) {
+            cells.push(row.migration_status().unwrap_or_default());
+        }
+
+        output.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+
+    if !failures.is_empty() {
+        output.push_str("\n## Failures\n\n");
+
+        for failure in failures {
+            output.push_str(&format!("* `{}`: {}\n", failure.file, failure.reason));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+fn write_results_directory() -> tempfile::TempDir {
+    use crate::enums::OutputFormat;
+    use crate::sizer_report::SizerReport;
+
+    let directory = tempfile::tempdir().unwrap();
+
+    let small = Report::new(SizerReport {
+        unique_blob_size: 13,
+        max_blob_size: 13,
+        ..Default::default()
+    });
+    let large = Report::new(SizerReport {
+        unique_blob_size: 2048,
+        max_blob_size: 1024,
+        ..Default::default()
+    });
+
+    fs::write(
+        directory.path().join("github-small.txt"),
+        small.render(OutputFormat::Text),
+    )
+    .unwrap();
+    fs::write(
+        directory.path().join("github-large.txt"),
+        large.render(OutputFormat::Json),
+    )
+    .unwrap();
+    fs::write(directory.path().join("github-empty.txt"), "").unwrap();
+    fs::write(
+        directory.path().join("github-large.wiki.txt"),
+        small.render(OutputFormat::Text),
+    )
+    .unwrap();
+    fs::write(directory.path().join("notes.md"), "Not a result file").unwrap();
+
+    directory
+}
+
+#[test]
+fn aggregate_sorts_rows_by_metric_and_lists_failures() {
+    let directory = write_results_directory();
+
+    let (rows, failures) = read_directory(
+        directory.path().to_str().unwrap(),
+        "${owner}-${repository}.txt",
+        Metric::UniqueBlobSize,
+    )
+    .unwrap();
+
+    assert_eq!(
+        rows.iter()
+            .map(|row| row.repository.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["github/large", "github/small"]
+    );
+    assert_eq!(
+        failures,
+        vec![Failure {
+            file: "github-empty.txt".to_string(),
+            reason: "The file is empty".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn aggregate_outputs_csv() {
+    let directory = write_results_directory();
+    let mut stderr = Vec::new();
+
+    let csv = call(
+        directory.path().to_str().unwrap(),
+        "${owner}-${repository}.txt",
+        Metric::MaxBlobSize,
+        AggregateFormat::Csv,
+        &mut stderr,
+    )
+    .unwrap();
+
+    assert_eq!(
+        csv,
+        "repository,unique_commit_count,unique_tree_count,unique_blob_count,unique_blob_size,max_blob_size,max_tree_entries,reference_count,max_history_depth,max_path_depth,level_of_concern\n\
+         github/large,0,0,0,2048,1024,0,0,0,0,0\n\
+         github/small,0,0,0,13,13,0,0,0,0,0\n"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&stderr),
+        format!(
+            "Aggregating results for 2 repositories from {} (1 files could not be read)\nFailed to read github-empty.txt: The file is empty\n",
+            directory.path().display()
+        )
+    );
+}
+
+#[test]
+fn aggregate_outputs_markdown() {
+    let directory = write_results_directory();
+    let mut stderr = Vec::new();
+
+    let markdown = call(
+        directory.path().to_str().unwrap(),
+        "${owner}-${repository}.txt",
+        Metric::MaxBlobSize,
+        AggregateFormat::Markdown,
+        &mut stderr,
+    )
+    .unwrap();
+
+    insta::assert_yaml_snapshot!(markdown);
+}
+
+#[test]
+fn aggregate_outputs_json() {
+    let directory = write_results_directory();
+    let mut stderr = Vec::new();
+
+    let json = call(
+        directory.path().to_str().unwrap(),
+        "${owner}-${repository}.txt",
+        Metric::MaxBlobSize,
+        AggregateFormat::Json,
+        &mut stderr,
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(json["repositories"][0]["repository"], "github/large");
+    assert_eq!(json["repositories"][0]["max_blob_size"], 1024);
+    assert_eq!(json["failures"][0]["file"], "github-empty.txt");
+}
+
+#[test]
+fn aggregate_lists_json_which_isnt_a_report_as_a_failure() {
+    let directory = tempfile::tempdir().unwrap();
+
+    fs::write(directory.path().join("github-other.txt"), "{}").unwrap();
+
+    let (rows, failures) = read_directory(
+        directory.path().to_str().unwrap(),
+        "${owner}-${repository}.txt",
+        Metric::UniqueBlobSize,
+    )
+    .unwrap();
+
+    assert!(rows.is_empty());
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].file, "github-other.txt");
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::history::History;
+use crate::sizer_report::format_bytes;
+
+/// The total size of the unique blobs ever stored under a path in a repository's history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PathSize {
+    /// The path's name within its parent directory, with a trailing slash for directories
+    pub name: String,
+    /// The total size of the unique blobs stored under the path, in bytes
+    pub size: u64,
+    pub blob_count: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PathSize>,
+}
+
+#[derive(Default)]
+struct Node<'a> {
+    blobs: HashSet<&'a str>,
+    children: BTreeMap<String, Node<'a>>,
+}
+
+/// Breaks down the size of a repository's history by path, down to a number of levels of
+/// directories. Each blob is counted once under every path it has been stored at, so a blob
+/// stored in two directories counts towards both of them.
+pub fn breakdown(history: &History, depth: usize) -> Vec<PathSize> {
+    let mut root = Node::default();
+
+    for commit in &history.commits {
+        for change in &commit.changes {
+            let components: Vec<&str> = change.path.split('/').collect();
+            let mut node = &mut root;
+
+            for (index, component) in components.iter().take(depth).enumerate() {
+                let name = if index + 1 < components.len() {
+                    format!("{}/", component)
+                } else {
+                    component.to_string()
+                };
+
+                node = node.children.entry(name).or_default();
+                node.blobs.insert(&change.blob);
+            }
+        }
+    }
+
+    path_sizes(root.children, history)
+}
+
+fn path_sizes(nodes: BTreeMap<String, Node>, history: &History) -> Vec<PathSize> {
+    let mut path_sizes: Vec<PathSize> = nodes
+        .into_iter()
+        .map(|(name, node)| PathSize {
+            name,
+            size: node.blobs.iter().map(|blob| history.blob_size(blob)).sum(),
+            blob_count: node.blobs.len(),
+            children: path_sizes(node.children, history),
+        })
+        .collect();
+
+    // `sort_by_key` is stable, so paths of the same size stay in alphabetical order
+    path_sizes.sort_by_key(|path_size| Reverse(path_size.size));
+    path_sizes
+}
+
+/// Renders a breakdown as a tree, like the output of `tree`
+pub fn to_text(path_sizes: &[PathSize]) -> String {
+    let mut output = String::new();
+    push_tree(&mut output, path_sizes, "");
+    output
+}
+
+fn push_tree(output: &mut String, path_sizes: &[PathSize], indent: &str) {
+    for (index, path_size) in path_sizes.iter().enumerate() {
+        let is_last = index + 1 == path_sizes.len();
+
+        output.push_str(&format!(
+            "{}{}{} {} ({})\n",
+            indent,
+            if is_last { "└── " } else { "├── " },
+            path_size.name,
+            format_bytes(path_size.size),
+            count_blobs(path_size.blob_count)
+        ));
+
+        push_tree(
+            output,
+            &path_size.children,
+            &format!("{}{}", indent, if is_last { "    " } else { "│   " }),
+        );
+    }
+}
+
+/// Describes a number of blobs, e.g. `1 blob` or `2 blobs`
+fn count_blobs(count: usize) -> String {
+    if count == 1 {
+        "1 blob".to_string()
+    } else {
+        format!("{} blobs", count)
+    }
+}
+
+#[test]
+fn breakdown_sums_unique_blobs_by_path() {
+    let directory = tempfile::tempdir().unwrap();
+    let history = History::read(
+        &crate::history::example_repository(directory.path()),
+        &crate::ref_selection::RefSelection::default(),
+    )
+    .unwrap();
+
+    let path_sizes = breakdown(&history, 2);
+
+    assert_eq!(
+        to_text(&path_sizes),
+        "├── assets/ 6.84 KiB (2 blobs)\n\
+         │   ├── video.mp4 4.88 KiB (1 blob)\n\
+         │   ├── copy of logo.png 1.95 KiB (1 blob)\n\
+         │   └── logo.png 1.95 KiB (1 blob)\n\
+         └── README.md 10 B (1 blob)\n"
+    );
+
+    let path_sizes = breakdown(&history, 1);
+
+    assert_eq!(path_sizes[0].name, "assets/");
+    assert_eq!(path_sizes[0].size, 7000);
+    assert!(path_sizes[0].children.is_empty());
+}
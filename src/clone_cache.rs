@@ -0,0 +1,475 @@
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::repository_sizer::is_git_repository;
+use crate::scan::find_repositories;
+
+pub const DEFAULT_HOSTNAME: &str = "github.com";
+
+// Written into each cached repository whenever it is cloned or refreshed, so the least
+// recently used repositories can be pruned first
+const LAST_USED_MARKER: &str = "gh-sizer-last-used";
+
+/// A directory of bare clones, kept between runs so that repositories only need to be cloned
+/// once and can then be refreshed with an incremental fetch. Clones are stored at
+/// `<directory>/<hostname>/<owner>/<repository>.git`.
+#[derive(Clone, Debug)]
+pub struct CloneCache {
+    pub directory: PathBuf,
+}
+
+/// A repository stored in the cache
+#[derive(Debug, PartialEq)]
+pub struct CacheEntry {
+    /// The hostname, owner and repository name, e.g. `github.com/timrogers/gh-sizer`
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+impl CloneCache {
+    pub fn new(directory: impl Into<PathBuf>) -> CloneCache {
+        CloneCache {
+            directory: directory.into(),
+        }
+    }
+
+    /// The path where a repository is, or would be, cached. The hostname, owner and repository
+    /// name must each be a single directory name, so the path can't point outside the cache.
+    pub fn path_for(&self, hostname: &str, nwo: &str) -> Result<PathBuf, Error> {
+        let segments: Vec<&str> = nwo.trim_end_matches(".git").split('/').collect();
+
+        let [owner, name] = segments.as_slice() else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "`{}` can't be cached, as it isn't in `owner/repository` format",
+                    nwo
+                ),
+            ));
+        };
+
+        for segment in [hostname, owner, name] {
+            if !is_single_directory_name(segment) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "`{}` on {} can't be cached, as `{}` isn't a valid directory name",
+                        nwo, hostname, segment
+                    ),
+                ));
+            }
+        }
+
+        Ok(self
+            .directory
+            .join(hostname)
+            .join(owner)
+            .join(format!("{}.git", name)))
+    }
+
+    /// Makes sure that an up-to-date bare clone of a repository is in the cache, cloning it with
+    /// `clone` if it isn't cached yet or fetching any new history if it is, and returns its path
+    pub fn fetch(
+        &self,
+        hostname: &str,
+        nwo: &str,
+        clone: impl FnOnce(&Path) -> Result<(), Error>,
+    ) -> Result<PathBuf, Error> {
+        let path = self.path_for(hostname, nwo)?;
+
+        if path.join(LAST_USED_MARKER).is_file() {
+            eprintln!("Fetching updates to {} into the clone cache...", nwo);
+            refresh(&path)?;
+        } else {
+            // A leftover directory without a marker is from a clone that didn't finish. Symlinks
+            // are resolved first, so nothing outside the cache is ever removed.
+            if path.exists() {
+                if !fs::canonicalize(&path)?.starts_with(fs::canonicalize(&self.directory)?) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "{} isn't inside the clone cache at {}, so it won't be replaced",
+                            path.display(),
+                            self.directory.display()
+                        ),
+                    ));
+                }
+
+                fs::remove_dir_all(&path)?;
+            }
+
+            fs::create_dir_all(&path)?;
+
+            if let Err(e) = clone(&path) {
+                let _ = fs::remove_dir_all(&path);
+                return Err(e);
+            }
+        }
+
+        fs::write(path.join(LAST_USED_MARKER), "")?;
+
+        Ok(path)
+    }
+
+    /// Lists the repositories in the cache, most recently used first
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, Error> {
+        if !self.directory.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut entries = Vec::new();
+
+        for path in find_repositories(&self.directory)? {
+            let name = path
+                .strip_prefix(&self.directory)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let last_used = fs::metadata(path.join(LAST_USED_MARKER))
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+
+            entries.push(CacheEntry {
+                name: name.trim_end_matches(".git").to_string(),
+                size: directory_size(&path)?,
+                path,
+                last_used,
+            });
+        }
+
+        entries.sort_by(|a, b| b.last_used.cmp(&a.last_used).then(a.name.cmp(&b.name)));
+
+        Ok(entries)
+    }
+
+    /// Removes the least recently used repositories from the cache until its total size is no
+    /// more than `max_size` bytes, and returns the removed repositories
+    pub fn prune(&self, max_size: u64) -> Result<Vec<CacheEntry>, Error> {
+        let mut entries = self.entries()?;
+        let mut total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+        let mut removed = Vec::new();
+
+        while total_size > max_size {
+            let Some(entry) = entries.pop() else {
+                break;
+            };
+
+            fs::remove_dir_all(&entry.path)?;
+            total_size -= entry.size;
+            removed.push(entry);
+        }
+
+        if self.directory.is_dir() {
+            remove_empty_directories(&self.directory)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Removes every repository from the cache, and returns the removed repositories
+    pub fn clear(&self) -> Result<Vec<CacheEntry>, Error> {
+        self.prune(0)
+    }
+}
+
+/// Whether a hostname, owner or repository name is a single, ordinary directory name, rather
+/// than e.g. `..` or a path with separators
+fn is_single_directory_name(segment: &str) -> bool {
+    let mut components = Path::new(segment).components();
+
+    !segment.contains(['/', '\\'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
+
+fn refresh(path: &Path) -> Result<(), Error> {
+    // Bare clones don't have a fetch refspec configured, so pass the refs which `git clone
+    // --bare` copies explicitly. `gh` is used as the credential helper, as it is for `gh repo
+    // clone`, so private repositories can be fetched.
+    let fetch_output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("-c")
+        .arg("credential.helper=")
+        .arg("-c")
+        .arg("credential.helper=!gh auth git-credential")
+        .arg("fetch")
+        .arg("--prune")
+        .arg("--quiet")
+        .arg("origin")
+        .arg("+refs/heads/*:refs/heads/*")
+        .arg("+refs/tags/*:refs/tags/*")
+        .output()?;
+
+    if !fetch_output.status.success() {
+        return Err(Error::other(
+            String::from_utf8_lossy(&fetch_output.stderr).to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn directory_size(path: &Path) -> Result<u64, Error> {
+    let mut size = 0;
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            size += directory_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+
+    Ok(size)
+}
+
+// Tidies up the hostname and owner directories left behind once all of their repositories
+// have been removed
+fn remove_empty_directories(path: &Path) -> Result<(), Error> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() && !is_git_repository(&entry.path()) {
+            remove_empty_directories(&entry.path())?;
+
+            match fs::remove_dir(entry.path()) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::DirectoryNotEmpty => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+fn add_cached_repository(cache: &CloneCache, nwo: &str, last_used: SystemTime) {
+    cache
+        .fetch(DEFAULT_HOSTNAME, nwo, |path| {
+            let status = Command::new("git")
+                .arg("init")
+                .arg("--quiet")
+                .arg("--bare")
+                .arg(path)
+                .status()?;
+            assert!(status.success());
+            Ok(())
+        })
+        .unwrap();
+
+    fs::File::options()
+        .write(true)
+        .open(
+            cache
+                .path_for(DEFAULT_HOSTNAME, nwo)
+                .unwrap()
+                .join(LAST_USED_MARKER),
+        )
+        .unwrap()
+        .set_modified(last_used)
+        .unwrap();
+}
+
+#[test]
+fn path_for_keys_clones_by_hostname_owner_and_repository() {
+    let cache = CloneCache::new("/var/cache/gh-sizer");
+
+    assert_eq!(
+        cache.path_for("github.com", "timrogers/gh-sizer").unwrap(),
+        PathBuf::from("/var/cache/gh-sizer/github.com/timrogers/gh-sizer.git")
+    );
+}
+
+#[test]
+fn path_for_rejects_names_which_would_point_outside_the_cache() {
+    let cache = CloneCache::new("/var/cache/gh-sizer");
+
+    for (hostname, nwo) in [
+        ("github.com", "../../x"),
+        ("github.com", "timrogers/.."),
+        ("github.com", "timrogers/gh-sizer/extra"),
+        ("github.com", "gh-sizer"),
+        ("..", "timrogers/gh-sizer"),
+        ("github.com/..", "timrogers/gh-sizer"),
+        ("", "timrogers/gh-sizer"),
+    ] {
+        assert_eq!(
+            cache.path_for(hostname, nwo).unwrap_err().kind(),
+            ErrorKind::InvalidInput,
+            "{} {}",
+            hostname,
+            nwo
+        );
+    }
+}
+
+#[test]
+fn fetch_rejects_names_outside_the_cache_without_removing_anything() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = CloneCache::new(directory.path().join("cache"));
+    let outside = directory.path().join("x.git");
+
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("keep"), "").unwrap();
+
+    let error = cache
+        .fetch(DEFAULT_HOSTNAME, "../../x", |_| {
+            panic!("a repository outside the cache should not be cloned")
+        })
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    assert!(outside.join("keep").exists());
+}
+
+#[test]
+fn fetch_removes_partial_clone_when_clone_fails() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = CloneCache::new(directory.path());
+
+    let error = cache
+        .fetch(DEFAULT_HOSTNAME, "github/broken-repo", |_| {
+            Err(Error::other("clone failed"))
+        })
+        .unwrap_err();
+
+    assert_eq!(error.to_string(), "clone failed");
+    assert!(!cache
+        .path_for(DEFAULT_HOSTNAME, "github/broken-repo")
+        .unwrap()
+        .exists());
+}
+
+#[test]
+fn prune_removes_least_recently_used_repositories_until_within_budget() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = CloneCache::new(directory.path());
+    let now = SystemTime::now();
+
+    add_cached_repository(
+        &cache,
+        "github/oldest",
+        now - std::time::Duration::from_secs(300),
+    );
+    add_cached_repository(
+        &cache,
+        "github/older",
+        now - std::time::Duration::from_secs(200),
+    );
+    add_cached_repository(&cache, "octocat/newest", now);
+
+    let entries = cache.entries().unwrap();
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec![
+            "github.com/octocat/newest",
+            "github.com/github/older",
+            "github.com/github/oldest"
+        ]
+    );
+
+    let budget = entries[0].size + entries[1].size;
+    let removed = cache.prune(budget).unwrap();
+
+    assert_eq!(
+        removed
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["github.com/github/oldest"]
+    );
+    assert_eq!(cache.entries().unwrap().len(), 2);
+
+    cache.clear().unwrap();
+
+    assert!(cache.entries().unwrap().is_empty());
+    assert!(!directory.path().join("github.com").exists());
+}
+
+#[test]
+fn fetch_refreshes_cached_repository_with_new_history() {
+    let directory = tempfile::tempdir().unwrap();
+    let origin = directory.path().join("origin");
+    let cache = CloneCache::new(directory.path().join("cache"));
+
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&origin)
+            .args([
+                "-c",
+                "user.name=gh-sizer",
+                "-c",
+                "user.email=gh-sizer@example.com",
+            ])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+
+    fs::create_dir_all(&origin).unwrap();
+    git(&["init", "--quiet", "--initial-branch=main"]);
+    git(&[
+        "commit",
+        "--quiet",
+        "--allow-empty",
+        "--message",
+        "First commit",
+    ]);
+
+    let clone = |path: &Path| {
+        let status = Command::new("git")
+            .arg("clone")
+            .arg("--quiet")
+            .arg("--bare")
+            .arg(&origin)
+            .arg(path)
+            .status()?;
+        assert!(status.success());
+        Ok(())
+    };
+
+    let path = cache
+        .fetch(DEFAULT_HOSTNAME, "github/origin", clone)
+        .unwrap();
+
+    git(&[
+        "commit",
+        "--quiet",
+        "--allow-empty",
+        "--message",
+        "Second commit",
+    ]);
+
+    let refreshed_path = cache
+        .fetch(DEFAULT_HOSTNAME, "github/origin", |_| {
+            panic!("cached repository should not be cloned again")
+        })
+        .unwrap();
+    assert_eq!(path, refreshed_path);
+
+    let log_output = Command::new("git")
+        .arg("-C")
+        .arg(&path)
+        .args(["log", "--format=%s", "main"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&log_output.stdout),
+        "Second commit\nFirst commit\n"
+    );
+}
@@ -0,0 +1,196 @@
+use serde::Deserialize;
+use std::io::{Error, ErrorKind};
+
+use crate::gh;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+const ORGANIZATIONS_QUERY: &str = "query($slug: String!, $endCursor: String) {
+  enterprise(slug: $slug) {
+    organizations(first: 100, after: $endCursor) {
+      nodes { login }
+      pageInfo { hasNextPage endCursor }
+    }
+  }
+}";
+
+#[derive(Deserialize)]
+struct Response {
+    data: Option<Data>,
+    #[serde(default)]
+    errors: Vec<GraphQLError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQLError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    enterprise: Option<Enterprise>,
+}
+
+#[derive(Deserialize)]
+struct Enterprise {
+    organizations: Organizations,
+}
+
+#[derive(Deserialize)]
+struct Organizations {
+    nodes: Vec<Organization>,
+}
+
+#[derive(Deserialize)]
+struct Organization {
+    login: String,
+}
+
+#[cfg_attr(test, automock)]
+pub trait EnterpriseOrganizationLister {
+    /// Lists the logins of the organizations which belong to an enterprise
+    fn call(&self, enterprise: &str) -> Result<Vec<String>, Error>;
+}
+
+pub struct EnterpriseOrganizationListerImpl {
+    /// The GitHub host the enterprise is on, or `None` for the default host
+    pub hostname: Option<String>,
+    /// The `gh` executable to run, which can be replaced with a fake for testing
+    pub gh_command: String,
+}
+
+impl EnterpriseOrganizationLister for EnterpriseOrganizationListerImpl {
+    fn call(&self, enterprise: &str) -> Result<Vec<String>, Error> {
+        let api_command_output = gh::command(&self.gh_command, self.hostname.as_deref())
+            .arg("api")
+            .arg("graphql")
+            .arg("--paginate")
+            .arg("-f")
+            .arg(format!("query={}", ORGANIZATIONS_QUERY))
+            .arg("-F")
+            .arg(format!("slug={}", enterprise))
+            .output()?;
+
+        // GraphQL errors, e.g. for an unknown enterprise, are returned in the response body, so
+        // parse it before checking whether `gh` succeeded
+        let output_text = String::from_utf8_lossy(&api_command_output.stdout);
+
+        match parse_organizations(&output_text, enterprise) {
+            Ok(organizations) if api_command_output.status.success() => Ok(organizations),
+            Err(e) if !output_text.trim().is_empty() => Err(e),
+            _ => Err(Error::other(
+                String::from_utf8_lossy(&api_command_output.stderr).to_string(),
+            )),
+        }
+    }
+}
+
+/// Parses the output of `gh api graphql --paginate` for the organizations query, which is one
+/// JSON response per page
+pub fn parse_organizations(output: &str, enterprise: &str) -> Result<Vec<String>, Error> {
+    let mut organizations = Vec::new();
+
+    for response in serde_json::Deserializer::from_str(output).into_iter::<Response>() {
+        let response = response.map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to parse the list of organizations from `gh`: {}", e),
+            )
+        })?;
+
+        if let Some(error) = response.errors.first() {
+            return Err(Error::other(format!(
+                "Failed to list the organizations in the {} enterprise: {}",
+                enterprise, error.message
+            )));
+        }
+
+        let Some(enterprise_data) = response.data.and_then(|data| data.enterprise) else {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "Couldn't find the {} enterprise, or you don't have access to it",
+                    enterprise
+                ),
+            ));
+        };
+
+        organizations.extend(
+            enterprise_data
+                .organizations
+                .nodes
+                .into_iter()
+                .map(|organization| organization.login),
+        );
+    }
+
+    Ok(organizations)
+}
+
+#[test]
+fn parse_organizations_combines_pages() {
+    let output = r#"{"data":{"enterprise":{"organizations":{"nodes":[{"login":"org-a"},{"login":"org-b"}],"pageInfo":{"hasNextPage":true,"endCursor":"Y3Vyc29y"}}}}}
+{"data":{"enterprise":{"organizations":{"nodes":[{"login":"org-c"}],"pageInfo":{"hasNextPage":false,"endCursor":null}}}}}"#;
+
+    assert_eq!(
+        parse_organizations(output, "acme").unwrap(),
+        vec!["org-a", "org-b", "org-c"]
+    );
+}
+
+#[test]
+fn parse_organizations_errors_for_unknown_enterprise() {
+    let output = r#"{"data":{"enterprise":null},"errors":[{"type":"NOT_FOUND","message":"Could not resolve to a Enterprise with the slug of 'nope'."}]}"#;
+
+    assert_eq!(
+        parse_organizations(output, "nope").unwrap_err().to_string(),
+        "Failed to list the organizations in the nope enterprise: Could not resolve to a Enterprise with the slug of 'nope'."
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn enterprise_organization_lister_reads_organizations_from_gh() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let directory = tempfile::tempdir().unwrap();
+    let fake_gh = directory.path().join("gh");
+
+    // A fake `gh` which records its arguments and returns a canned response
+    let script = directory.path().join("gh.sh");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\nprintf '%s\\n' \"$@\" > {}\ncat <<'JSON'\n{}\nJSON\n",
+            directory.path().join("arguments").display(),
+            r#"{"data":{"enterprise":{"organizations":{"nodes":[{"login":"org-a"},{"login":"org-b"}],"pageInfo":{"hasNextPage":false,"endCursor":null}}}}}"#
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    // Copy the script with `cp`, so that this process never has the executable open for
+    // writing, which would make running it fail with "Text file busy" if another test forks
+    // at the same time
+    assert!(std::process::Command::new("cp")
+        .arg("-p")
+        .arg(&script)
+        .arg(&fake_gh)
+        .status()
+        .unwrap()
+        .success());
+
+    let organizations = EnterpriseOrganizationListerImpl {
+        hostname: None,
+        gh_command: fake_gh.to_str().unwrap().to_string(),
+    }
+    .call("acme")
+    .unwrap();
+
+    assert_eq!(organizations, vec!["org-a", "org-b"]);
+
+    let arguments = std::fs::read_to_string(directory.path().join("arguments")).unwrap();
+    assert!(arguments.starts_with("api\ngraphql\n--paginate\n"));
+    assert!(arguments.ends_with("-F\nslug=acme\n"));
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -29,3 +30,73 @@ impl fmt::Display for ScriptType {
         }
     }
 }
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum AggregateFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl fmt::Display for AggregateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AggregateFormat::Csv => write!(f, "csv"),
+            AggregateFormat::Json => write!(f, "json"),
+            AggregateFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Visibility {
+    Public,
+    Private,
+    Internal,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Private => write!(f, "private"),
+            Visibility::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SortBy {
+    /// Largest first, according to the disk usage reported by the GitHub API
+    Size,
+    /// Alphabetically by owner and repository name
+    Name,
+    /// Most recently pushed to first
+    Pushed,
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortBy::Size => write!(f, "size"),
+            SortBy::Name => write!(f, "name"),
+            SortBy::Pushed => write!(f, "pushed"),
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interval {
+    Month,
+    Year,
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Interval::Month => write!(f, "month"),
+            Interval::Year => write!(f, "year"),
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use serde::Serialize;
+use std::fmt;
+use std::io::{Error, ErrorKind, Write};
+
+use crate::enums::OutputFormat;
+use crate::gh;
+use crate::github_repository_lister::Repository;
+use crate::sizer_report::{format_bytes, to_json_string};
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+const MEBIBYTE: u64 = 1024 * 1024;
+const GIBIBYTE: u64 = 1024 * MEBIBYTE;
+
+const ESTIMATE_NOTE: &str = "These sizes are based on the disk usage reported by the GitHub API, \
+                             so they are only approximate. Run without --estimate to analyze \
+                             repos with git-sizer.\n";
+
+/// A rough classification of a repository's size, for deciding which repositories need a full
+/// analysis with `git-sizer`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeTier {
+    /// Less than 100 MiB
+    Small,
+    /// Less than 1 GiB
+    Medium,
+    /// Less than 10 GiB
+    Large,
+    /// 10 GiB or more
+    VeryLarge,
+}
+
+impl SizeTier {
+    pub fn for_size(size: u64) -> SizeTier {
+        if size < 100 * MEBIBYTE {
+            SizeTier::Small
+        } else if size < GIBIBYTE {
+            SizeTier::Medium
+        } else if size < 10 * GIBIBYTE {
+            SizeTier::Large
+        } else {
+            SizeTier::VeryLarge
+        }
+    }
+}
+
+impl fmt::Display for SizeTier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SizeTier::Small => write!(f, "small"),
+            SizeTier::Medium => write!(f, "medium"),
+            SizeTier::Large => write!(f, "large"),
+            SizeTier::VeryLarge => write!(f, "very large"),
+        }
+    }
+}
+
+/// An approximate size for a repository, based on the disk usage reported by the GitHub API
+/// rather than an analysis of a clone
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Estimate {
+    pub repository: String,
+    /// The repository's disk usage in bytes, as reported by the GitHub API. This is the
+    /// compressed size of the repository on the server, so it is much smaller than the
+    /// uncompressed sizes reported by `git-sizer`.
+    pub disk_usage: u64,
+    pub tier: SizeTier,
+}
+
+impl Estimate {
+    /// Builds an estimate from the disk usage reported by the API, which is in kilobytes
+    pub fn from_disk_usage(repository: &str, disk_usage_in_kilobytes: u64) -> Estimate {
+        let disk_usage = disk_usage_in_kilobytes * 1024;
+
+        Estimate {
+            repository: repository.to_string(),
+            disk_usage,
+            tier: SizeTier::for_size(disk_usage),
+        }
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => format!("{}\n{}", self.to_text(), ESTIMATE_NOTE),
+            OutputFormat::Json => to_json_string(self),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} is approximately {} ({})\n",
+            self.repository,
+            format_bytes(self.disk_usage),
+            self.tier
+        )
+    }
+}
+
+/// Renders the estimates for several repositories, either one line per repository or as a
+/// JSON array
+pub fn render_all(estimates: &[Estimate], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => format!(
+            "{}\n{}",
+            estimates.iter().map(Estimate::to_text).collect::<String>(),
+            ESTIMATE_NOTE
+        ),
+        OutputFormat::Json => to_json_string(&estimates),
+    }
+}
+
+/// Estimates the size of each of a list of repositories, reporting any failures, and returns the
+/// estimates along with the number of failures. Repositories listed with their disk usage, e.g.
+/// by `gh repo list`, are estimated straight away, so the API is only called for repositories
+/// listed without it, e.g. in a repos file.
+pub fn estimate_all(
+    repositories: &[Repository],
+    repository_estimator: &impl RepositoryEstimator,
+    stderr: &mut impl Write,
+) -> Result<(Vec<Estimate>, usize), Error> {
+    let mut estimates = Vec::new();
+    let mut failed_count = 0;
+
+    for repository in repositories {
+        let estimate = match repository.disk_usage {
+            Some(disk_usage) => Ok(Estimate::from_disk_usage(&repository.nwo(), disk_usage)),
+            None => repository_estimator.call(&repository.nwo()),
+        };
+
+        match estimate {
+            Ok(estimate) => estimates.push(estimate),
+            Err(e) => {
+                writeln!(
+                    stderr,
+                    "Failed to estimate the size of repo {}: {}",
+                    repository.nwo(),
+                    e.to_string().trim_end()
+                )?;
+                failed_count += 1;
+            }
+        }
+    }
+
+    Ok((estimates, failed_count))
+}
+
+#[cfg_attr(test, automock)]
+pub trait RepositoryEstimator {
+    fn call(&self, nwo: &str) -> Result<Estimate, Error>;
+}
+
+pub struct RepositoryEstimatorImpl {
+    /// The GitHub host the repository is on, or `None` for the default host
+    pub hostname: Option<String>,
+    /// The `gh` executable to run, which can be replaced with a fake for testing
+    pub gh_command: String,
+}
+
+impl RepositoryEstimator for RepositoryEstimatorImpl {
+    fn call(&self, nwo: &str) -> Result<Estimate, Error> {
+        let view_command_output = gh::command(&self.gh_command, self.hostname.as_deref())
+            .arg("repo")
+            .arg("view")
+            .arg(nwo)
+            .arg("--json")
+            .arg("diskUsage")
+            .output()?;
+
+        if !view_command_output.status.success() {
+            return Err(Error::other(
+                String::from_utf8_lossy(&view_command_output.stderr).to_string(),
+            ));
+        }
+
+        parse_estimate(&String::from_utf8_lossy(&view_command_output.stdout), nwo)
+    }
+}
+
+/// Parses the output of `gh repo view --json diskUsage` into an estimate
+pub fn parse_estimate(json: &str, nwo: &str) -> Result<Estimate, Error> {
+    let repository: Repository = serde_json::from_str(json).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse the details of {} from `gh`: {}", nwo, e),
+        )
+    })?;
+
+    let disk_usage = repository.disk_usage.ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("The GitHub API didn't report a size for {}", nwo),
+        )
+    })?;
+
+    Ok(Estimate::from_disk_usage(nwo, disk_usage))
+}
+
+#[test]
+fn size_tier_boundaries() {
+    assert_eq!(SizeTier::for_size(0), SizeTier::Small);
+    assert_eq!(SizeTier::for_size(100 * MEBIBYTE - 1), SizeTier::Small);
+    assert_eq!(SizeTier::for_size(100 * MEBIBYTE), SizeTier::Medium);
+    assert_eq!(SizeTier::for_size(GIBIBYTE), SizeTier::Large);
+    assert_eq!(SizeTier::for_size(10 * GIBIBYTE), SizeTier::VeryLarge);
+}
+
+#[test]
+fn parse_estimate_converts_disk_usage_from_kilobytes() {
+    let estimate = parse_estimate(r#"{"diskUsage": 2097152}"#, "github/gh-sizer").unwrap();
+
+    assert_eq!(
+        estimate,
+        Estimate {
+            repository: "github/gh-sizer".to_string(),
+            disk_usage: 2 * GIBIBYTE,
+            tier: SizeTier::Large,
+        }
+    );
+    assert!(estimate
+        .to_text()
+        .starts_with("github/gh-sizer is approximately 2.00 GiB (large)\n"));
+    assert_eq!(
+        estimate.render(OutputFormat::Json),
+        "{\n    \"repository\": \"github/gh-sizer\",\n    \"disk_usage\": 2147483648,\n    \"tier\": \"large\"\n}"
+    );
+}
+
+#[test]
+fn render_all_lists_each_repository_once() {
+    let estimates = vec![
+        Estimate::from_disk_usage("github/gh-sizer", 512),
+        Estimate::from_disk_usage("github/linguist", 20 * 1024 * 1024),
+    ];
+
+    assert_eq!(
+        render_all(&estimates, OutputFormat::Text),
+        format!(
+            "github/gh-sizer is approximately 512 KiB (small)\n\
+             github/linguist is approximately 20.0 GiB (very large)\n\n{}",
+            ESTIMATE_NOTE
+        )
+    );
+}
+
+#[test]
+fn parse_estimate_errors_without_disk_usage() {
+    assert_eq!(
+        parse_estimate("{}", "github/gh-sizer")
+            .unwrap_err()
+            .to_string(),
+        "The GitHub API didn't report a size for github/gh-sizer"
+    );
+}
+
+#[test]
+fn estimate_all_only_calls_the_api_for_repositories_without_disk_usage() {
+    let mut estimator_mock = MockRepositoryEstimator::new();
+
+    estimator_mock
+        .expect_call()
+        .with(eq("github/from-file"))
+        .returning(|nwo| Ok(Estimate::from_disk_usage(nwo, 2048)))
+        .times(1);
+    estimator_mock
+        .expect_call()
+        .with(eq("github/missing"))
+        .returning(|_| Err(Error::other("Could not resolve to a Repository")))
+        .times(1);
+
+    let mut stderr = Vec::new();
+
+    let (estimates, failed_count) = estimate_all(
+        &[
+            Repository {
+                disk_usage: Some(512),
+                ..Repository::new("github", "listed")
+            },
+            Repository::new("github", "from-file"),
+            Repository::new("github", "missing"),
+        ],
+        &estimator_mock,
+        &mut stderr,
+    )
+    .unwrap();
+
+    assert_eq!(
+        estimates,
+        vec![
+            Estimate::from_disk_usage("github/listed", 512),
+            Estimate::from_disk_usage("github/from-file", 2048),
+        ]
+    );
+    assert_eq!(failed_count, 1);
+    assert_eq!(
+        String::from_utf8_lossy(&stderr),
+        "Failed to estimate the size of repo github/missing: Could not resolve to a Repository\n"
+    );
+}
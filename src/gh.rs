@@ -0,0 +1,27 @@
+use std::process::Command;
+
+/// Builds a `gh` command which targets a specific GitHub host, e.g. a GitHub Enterprise Server
+/// instance, or the default host if `hostname` is `None`
+pub fn command(gh_command: &str, hostname: Option<&str>) -> Command {
+    let mut command = Command::new(gh_command);
+
+    if let Some(hostname) = hostname {
+        command.env("GH_HOST", hostname);
+    }
+
+    command
+}
+
+#[test]
+fn command_sets_gh_host_when_hostname_is_provided() {
+    let command = command("gh", Some("github.example.com"));
+
+    assert_eq!(
+        command.get_envs().collect::<Vec<_>>(),
+        vec![(
+            std::ffi::OsStr::new("GH_HOST"),
+            Some(std::ffi::OsStr::new("github.example.com"))
+        )]
+    );
+    assert_eq!(self::command("gh", None).get_envs().count(), 0);
+}
@@ -0,0 +1,319 @@
+use serde::Deserialize;
+use std::fs;
+use std::io::{Error, ErrorKind, Write};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+use crate::enums::OutputFormat;
+use crate::generate_script::output_filename;
+use crate::gh;
+use crate::migration_readiness::Profile;
+use crate::org::{size_all, Summary, Target};
+use crate::policy::Policy;
+use crate::report::Report;
+use crate::repository_sizer::{size_repository_at, RepositorySizer};
+
+#[cfg(test)]
+use crate::repository_sizer::MockRepositorySizer;
+#[cfg(test)]
+use crate::sizer_report::SizerReport;
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+/// A gist, as returned by the GitHub API
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Gist {
+    pub id: String,
+    pub description: Option<String>,
+}
+
+/// Parses the gists listed by `gh api <endpoint> --jq '.[] | {id, description}'`, which prints
+/// one JSON object per line
+pub fn parse_gists(output: &str) -> Result<Vec<Gist>, Error> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Failed to parse the list of gists from `gh`: {}", e),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg_attr(test, automock)]
+pub trait GistLister {
+    fn call(&self, user: &str) -> Result<Vec<Gist>, Error>;
+}
+
+pub struct GistListerImpl {
+    /// The GitHub host to list gists on, or `None` for the default host
+    pub hostname: Option<String>,
+    /// The `gh` executable to run, which can be replaced with a fake for testing
+    pub gh_command: String,
+}
+
+impl GistListerImpl {
+    /// Calls the GitHub API with `gh api`, returning the output filtered by a jq expression
+    fn api(&self, endpoint: &str, jq: &str) -> Result<String, Error> {
+        let api_command_output = gh::command(&self.gh_command, self.hostname.as_deref())
+            .arg("api")
+            .arg("--paginate")
+            .arg(endpoint)
+            .arg("--jq")
+            .arg(jq)
+            .output()?;
+
+        if !api_command_output.status.success() {
+            return Err(Error::other(
+                String::from_utf8_lossy(&api_command_output.stderr).to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&api_command_output.stdout).to_string())
+    }
+}
+
+impl GistLister for GistListerImpl {
+    fn call(&self, user: &str) -> Result<Vec<Gist>, Error> {
+        // `users/{user}/gists` only lists a user's public gists, even if they are the
+        // authenticated user, so list the authenticated user's gists with `gists`, which
+        // includes their secret gists too
+        let authenticated_user = self.api("user", ".login")?;
+        let endpoint = if authenticated_user.trim().eq_ignore_ascii_case(user) {
+            "gists".to_string()
+        } else {
+            format!("users/{}/gists", user)
+        };
+
+        parse_gists(&self.api(&endpoint, ".[] | {id, description}")?)
+    }
+}
+
+pub struct GistSizerImpl {
+    /// The GitHub host to clone gists from, or `None` for the default host
+    pub hostname: Option<String>,
+    /// The `gh` executable to run, which can be replaced with a fake for testing
+    pub gh_command: String,
+}
+
+impl RepositorySizer for GistSizerImpl {
+    /// Makes a bare clone of a gist, identified by its ID, and runs `git-sizer` on it
+    fn call(&self, id: &str) -> Result<Report, Error> {
+        let temporary_directory = tempdir()?;
+
+        let clone_output = gh::command(&self.gh_command, self.hostname.as_deref())
+            .arg("gist")
+            .arg("clone")
+            .arg(id)
+            .arg(temporary_directory.path())
+            .arg("--")
+            .arg("--bare")
+            .output()?;
+
+        if !clone_output.status.success() {
+            return Err(Error::other(
+                String::from_utf8_lossy(&clone_output.stderr).to_string(),
+            ));
+        }
+
+        Ok(Report::new(size_repository_at(
+            temporary_directory.path(),
+            &[],
+        )?))
+    }
+}
+
+/// Sizes all of a user's gists, writing each one's report to a file named from a template,
+/// where `${owner}` is the user and `${repository}` is the gist's ID
+#[allow(clippy::too_many_arguments)]
+pub fn call(
+    user: &str,
+    jobs: usize,
+    output_format: OutputFormat,
+    output_directory: &str,
+    output_filename_template: &str,
+    policy: Option<&Policy>,
+    profile: Option<&Profile>,
+    gist_lister: &impl GistLister,
+    gist_sizer: &(impl RepositorySizer + Sync),
+    stderr: &mut (impl Write + Send),
+) -> Result<Summary, Error> {
+    let gists = gist_lister.call(user)?;
+
+    writeln!(
+        stderr,
+        "Sizing {} gists owned by {} using {} jobs",
+        gists.len(),
+        user,
+        jobs
+    )?;
+
+    fs::create_dir_all(output_directory)?;
+
+    let targets: Vec<Target> = gists
+        .iter()
+        .map(|gist| Target {
+            name: gist.id.clone(),
+            output_path: PathBuf::from(output_directory).join(output_filename(
+                output_filename_template,
+                user,
+                &gist.id,
+            )),
+        })
+        .collect();
+
+    let summary = size_all(
+        &targets,
+        "gist",
+        jobs,
+        output_format,
+        &|target| Ok(gist_sizer.call(&target.name)?.check(policy, profile)),
+        stderr,
+    );
+
+    summary.write_to(stderr, &format!("gists owned by {}", user))?;
+
+    Ok(summary)
+}
+
+#[test]
+fn parse_gists_reads_one_gist_per_line() {
+    assert_eq!(
+        parse_gists(
+            "{\"description\":\"Notes\",\"id\":\"aa5a315d61ae9438b18d\"}\n\
+             {\"description\":null,\"id\":\"5b0e0062eb8e9654adad\"}\n"
+        )
+        .unwrap(),
+        vec![
+            Gist {
+                id: "aa5a315d61ae9438b18d".to_string(),
+                description: Some("Notes".to_string()),
+            },
+            Gist {
+                id: "5b0e0062eb8e9654adad".to_string(),
+                description: None,
+            },
+        ]
+    );
+    assert!(parse_gists("").unwrap().is_empty());
+    assert!(parse_gists("not json").is_err());
+}
+
+#[test]
+fn gists_writes_output_for_each_gist_and_summarizes_results() {
+    let mut lister_mock = MockGistLister::new();
+
+    lister_mock
+        .expect_call()
+        .with(eq("octocat"))
+        .returning(|_| {
+            Ok(vec![
+                Gist {
+                    id: "aa5a315d61ae9438b18d".to_string(),
+                    ..Default::default()
+                },
+                Gist {
+                    id: "broken".to_string(),
+                    ..Default::default()
+                },
+            ])
+        });
+
+    let mut sizer_mock = MockRepositorySizer::new();
+
+    sizer_mock
+        .expect_call()
+        .returning(|id| match id {
+            "broken" => Err(Error::other("clone failed")),
+            _ => Ok(Report::new(SizerReport {
+                unique_commit_count: 1,
+                ..Default::default()
+            })),
+        })
+        .times(2);
+
+    let output_directory = tempfile::tempdir().unwrap();
+    let mut stderr = Vec::new();
+
+    let summary = call(
+        "octocat",
+        2,
+        OutputFormat::Json,
+        output_directory.path().to_str().unwrap(),
+        "${owner}-${repository}.json",
+        None,
+        None,
+        &lister_mock,
+        &sizer_mock,
+        &mut stderr,
+    )
+    .unwrap();
+
+    assert_eq!(summary.succeeded, vec!["aa5a315d61ae9438b18d".to_string()]);
+    assert_eq!(summary.failed.len(), 1);
+    assert!(fs::read_to_string(
+        output_directory
+            .path()
+            .join("octocat-aa5a315d61ae9438b18d.json")
+    )
+    .unwrap()
+    .contains("\"unique_commit_count\": 1"));
+
+    let stderr = String::from_utf8_lossy(&stderr);
+    assert!(stderr.starts_with("Sizing 2 gists owned by octocat using 2 jobs\n"));
+    assert!(stderr
+        .ends_with("Finished sizing 2 gists owned by octocat: 1 succeeded, 1 failed\n* broken\n"));
+}
+
+#[test]
+fn gists_checks_each_gist_against_the_policy() {
+    let mut lister_mock = MockGistLister::new();
+
+    lister_mock.expect_call().returning(|_| {
+        Ok(vec![Gist {
+            id: "aa5a315d61ae9438b18d".to_string(),
+            ..Default::default()
+        }])
+    });
+
+    let mut sizer_mock = MockRepositorySizer::new();
+
+    sizer_mock.expect_call().returning(|_| {
+        Ok(Report::new(SizerReport {
+            max_blob_size: 13,
+            ..Default::default()
+        }))
+    });
+
+    let output_directory = tempfile::tempdir().unwrap();
+    let policy = Policy::from_toml("[limits]\nmax_blob_size = 10\n").unwrap();
+    let mut stderr = Vec::new();
+
+    let summary = call(
+        "octocat",
+        1,
+        OutputFormat::Json,
+        output_directory.path().to_str().unwrap(),
+        "${repository}.json",
+        Some(&policy),
+        None,
+        &lister_mock,
+        &sizer_mock,
+        &mut stderr,
+    )
+    .unwrap();
+
+    assert_eq!(
+        summary.violated_policy,
+        vec!["aa5a315d61ae9438b18d".to_string()]
+    );
+    assert!(
+        fs::read_to_string(output_directory.path().join("aa5a315d61ae9438b18d.json"))
+            .unwrap()
+            .contains("\"policy_violations\"")
+    );
+}
@@ -1,28 +1,126 @@
-use std::io::Error;
-use std::process::Command;
+use serde::{Deserialize, Deserializer};
+use std::io::{Error, ErrorKind};
+
+use crate::gh;
 
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 
+// The metadata requested from `gh repo list` for each repository
+const REPOSITORY_FIELDS: &str =
+    "name,isFork,isArchived,visibility,primaryLanguage,repositoryTopics,pushedAt,diskUsage";
+
+/// A repository returned by `gh repo list`, with the metadata used to filter and sort
+/// repositories
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Repository {
+    /// The user or organization which owns the repository, which isn't part of the JSON
+    /// returned for each repository by `gh`
+    #[serde(skip)]
+    pub owner: String,
+    pub name: String,
+    /// The host from the repository's URL, if it was listed by URL in a repos file
+    #[serde(skip)]
+    pub host: Option<String>,
+    pub is_fork: bool,
+    pub is_archived: bool,
+    /// `PUBLIC`, `PRIVATE` or `INTERNAL`
+    pub visibility: String,
+    #[serde(deserialize_with = "deserialize_name")]
+    pub primary_language: Option<String>,
+    #[serde(deserialize_with = "deserialize_names")]
+    pub repository_topics: Vec<String>,
+    /// When the repository was last pushed to, as an ISO 8601 timestamp
+    pub pushed_at: Option<String>,
+    /// The size of the repository reported by the API, in kilobytes
+    pub disk_usage: Option<u64>,
+}
+
+impl Repository {
+    pub fn new(owner: &str, name: &str) -> Repository {
+        Repository {
+            owner: owner.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The repository's owner and name, separated by a slash, e.g. `timrogers/gh-sizer`
+    pub fn nwo(&self) -> String {
+        format!("{}/{}", self.owner, self.name)
+    }
+
+    /// The repository's disk usage in bytes, as reported by the GitHub API
+    pub fn size(&self) -> Option<u64> {
+        self.disk_usage.map(|disk_usage| disk_usage * 1024)
+    }
+}
+
+#[derive(Deserialize)]
+struct Named {
+    name: String,
+}
+
+// `gh` represents a repository's primary language as `{"name": "Rust"}`, or `null` if it
+// doesn't have one
+fn deserialize_name<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(Option::<Named>::deserialize(deserializer)?
+        .map(|named| named.name)
+        .filter(|name| !name.is_empty()))
+}
+
+// `gh` represents a repository's topics as `[{"name": "cli"}]`, or `null` if it has none
+fn deserialize_names<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(Option::<Vec<Named>>::deserialize(deserializer)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|named| named.name)
+        .collect())
+}
+
+/// Parses the JSON output of `gh repo list --json` for an owner's repositories
+pub fn parse_repositories(json: &str, owner: &str) -> Result<Vec<Repository>, Error> {
+    let repositories: Vec<Repository> = serde_json::from_str(json).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse the list of repositories from `gh`: {}", e),
+        )
+    })?;
+
+    Ok(repositories
+        .into_iter()
+        .map(|repository| Repository {
+            owner: owner.to_string(),
+            ..repository
+        })
+        .collect())
+}
+
 #[cfg_attr(test, automock)]
 pub trait GitHubRepositoryLister {
-    fn call(&self, owner: &str) -> Result<Vec<String>, Error>;
+    fn call(&self, owner: &str) -> Result<Vec<Repository>, Error>;
 }
 
-pub struct GitHubRepositoryListerImpl;
+pub struct GitHubRepositoryListerImpl {
+    /// The GitHub host to list repositories on, or `None` for the default host
+    pub hostname: Option<String>,
+    /// The `gh` executable to run, which can be replaced with a fake for testing
+    pub gh_command: String,
+}
 
 impl GitHubRepositoryLister for GitHubRepositoryListerImpl {
-    fn call(&self, owner: &str) -> Result<Vec<String>, Error> {
-        let mut list_command = Command::new("gh");
+    fn call(&self, owner: &str) -> Result<Vec<Repository>, Error> {
+        let mut list_command = gh::command(&self.gh_command, self.hostname.as_deref());
         list_command.arg("repo");
         list_command.arg("list");
         list_command.arg(owner);
         list_command.arg("-L");
         list_command.arg("10000");
         list_command.arg("--json");
-        list_command.arg("name");
-        list_command.arg("--jq");
-        list_command.arg(".[].name");
+        list_command.arg(REPOSITORY_FIELDS);
 
         let list_command_output = list_command.output()?;
 
@@ -32,11 +130,63 @@ impl GitHubRepositoryLister for GitHubRepositoryListerImpl {
         }
 
         let output_text = String::from_utf8_lossy(&list_command_output.stdout);
-        let output_lines: Vec<String> = output_text
-            .split_whitespace()
-            .map(|s| s.to_string())
-            .collect();
 
-        Ok(output_lines)
+        parse_repositories(&output_text, owner)
     }
 }
+
+#[test]
+fn parse_repositories_reads_gh_repo_list_output() {
+    let repositories = parse_repositories(
+        r#"[
+            {
+                "diskUsage": 1234,
+                "isArchived": false,
+                "isFork": true,
+                "name": "gh-sizer",
+                "primaryLanguage": {"name": "Rust"},
+                "pushedAt": "2024-03-01T12:00:00Z",
+                "repositoryTopics": [{"name": "cli"}, {"name": "git"}],
+                "visibility": "PUBLIC"
+            },
+            {
+                "diskUsage": 0,
+                "isArchived": true,
+                "isFork": false,
+                "name": "empty",
+                "primaryLanguage": null,
+                "pushedAt": null,
+                "repositoryTopics": null,
+                "visibility": "PRIVATE"
+            }
+        ]"#,
+        "timrogers",
+    )
+    .unwrap();
+
+    assert_eq!(
+        repositories,
+        vec![
+            Repository {
+                owner: "timrogers".to_string(),
+                name: "gh-sizer".to_string(),
+                is_fork: true,
+                is_archived: false,
+                visibility: "PUBLIC".to_string(),
+                primary_language: Some("Rust".to_string()),
+                repository_topics: vec!["cli".to_string(), "git".to_string()],
+                pushed_at: Some("2024-03-01T12:00:00Z".to_string()),
+                disk_usage: Some(1234),
+                ..Default::default()
+            },
+            Repository {
+                owner: "timrogers".to_string(),
+                name: "empty".to_string(),
+                is_archived: true,
+                visibility: "PRIVATE".to_string(),
+                disk_usage: Some(0),
+                ..Default::default()
+            },
+        ]
+    );
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::enums::Interval;
+use crate::history::History;
+use crate::sizer_report::format_bytes;
+
+/// The width of the longest bar in the text chart, in characters
+const BAR_WIDTH: u64 = 40;
+
+/// The objects first introduced to a repository during a month or year
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Period {
+    /// The month, e.g. `2024-01`, or year, e.g. `2024`
+    pub period: String,
+    pub commit_count: u64,
+    pub blob_count: u64,
+    /// The total size of the new blobs, in bytes
+    pub blob_size: u64,
+}
+
+/// How a repository has grown over time, based on the dates of the commits which introduced
+/// each object
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Growth {
+    pub interval: Interval,
+    /// Every period from the first commit to the last, including any without new objects
+    pub periods: Vec<Period>,
+}
+
+impl Growth {
+    /// Renders the growth as a bar chart of the size of the new blobs in each period
+    pub fn to_text(&self) -> String {
+        let max_blob_size = self
+            .periods
+            .iter()
+            .map(|period| period.blob_size)
+            .max()
+            .unwrap_or(0);
+
+        self.periods
+            .iter()
+            .map(|period| {
+                let bar_length = if period.blob_size == 0 {
+                    0
+                } else {
+                    // Round up, so periods with any growth at all have a visible bar
+                    (period.blob_size * BAR_WIDTH).div_ceil(max_blob_size)
+                };
+
+                format!(
+                    "{} |{:<width$}| {}, {}, {}\n",
+                    period.period,
+                    "#".repeat(bar_length as usize),
+                    format_bytes(period.blob_size),
+                    count_of(period.commit_count, "commit"),
+                    count_of(period.blob_count, "blob"),
+                    width = BAR_WIDTH as usize
+                )
+            })
+            .collect()
+    }
+}
+
+/// Describes a number of commits or blobs, e.g. `1 commit` or `2 commits`
+fn count_of(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Buckets the commits and blobs in a repository's history by the month or year they were
+/// introduced, using the commit date of the first commit to include each blob
+pub fn growth(history: &History, interval: Interval) -> Growth {
+    let mut periods: BTreeMap<String, Period> = BTreeMap::new();
+    let mut seen_blobs: HashSet<&str> = HashSet::new();
+
+    for commit in &history.commits {
+        let key = period_for(&commit.date, interval);
+        let period = periods.entry(key.clone()).or_insert_with(|| Period {
+            period: key,
+            ..Default::default()
+        });
+
+        period.commit_count += 1;
+
+        for change in &commit.changes {
+            if seen_blobs.insert(&change.blob) {
+                period.blob_count += 1;
+                period.blob_size += history.blob_size(&change.blob);
+            }
+        }
+    }
+
+    let (Some(first), Some(last)) = (periods.keys().next(), periods.keys().next_back()) else {
+        return Growth {
+            interval,
+            periods: Vec::new(),
+        };
+    };
+
+    // Fill in any periods without commits, so the time series has no gaps
+    let mut key = first.clone();
+    let last = last.clone();
+
+    while key < last {
+        key = next_period(&key, interval);
+        periods.entry(key.clone()).or_insert_with(|| Period {
+            period: key.clone(),
+            ..Default::default()
+        });
+    }
+
+    Growth {
+        interval,
+        periods: periods.into_values().collect(),
+    }
+}
+
+/// The month or year of an ISO 8601 timestamp, e.g. `2024-01` or `2024`
+fn period_for(date: &str, interval: Interval) -> String {
+    let length = match interval {
+        Interval::Month => 7,
+        Interval::Year => 4,
+    };
+
+    date.chars().take(length).collect()
+}
+
+fn next_period(period: &str, interval: Interval) -> String {
+    let (year, month) = period.split_once('-').unwrap_or((period, "12"));
+    let year: u32 = year.parse().unwrap_or(0);
+    let month: u32 = month.parse().unwrap_or(12);
+
+    match interval {
+        Interval::Month if month < 12 => format!("{:04}-{:02}", year, month + 1),
+        Interval::Month => format!("{:04}-01", year + 1),
+        Interval::Year => format!("{:04}", year + 1),
+    }
+}
+
+#[cfg(test)]
+fn example_history() -> History {
+    use crate::history::{Change, Commit};
+
+    let commit = |oid: &str, date: &str, blobs: &[&str]| Commit {
+        oid: oid.to_string(),
+        date: date.to_string(),
+        changes: blobs
+            .iter()
+            .map(|blob| Change {
+                path: format!("{}.txt", blob),
+                blob: blob.to_string(),
+            })
+            .collect(),
+    };
+
+    History {
+        commits: vec![
+            commit("c1", "2023-11-02T10:00:00+00:00", &["b1", "b2"]),
+            commit("c2", "2023-11-20T10:00:00+00:00", &["b1"]),
+            commit("c3", "2024-02-01T10:00:00-05:00", &["b3"]),
+        ],
+        blob_sizes: [("b1", 100), ("b2", 300), ("b3", 200)]
+            .into_iter()
+            .map(|(blob, size)| (blob.to_string(), size))
+            .collect(),
+    }
+}
+
+#[test]
+fn growth_buckets_new_objects_by_month_without_gaps() {
+    let growth = growth(&example_history(), Interval::Month);
+
+    assert_eq!(
+        growth
+            .periods
+            .iter()
+            .map(|period| (
+                period.period.as_str(),
+                period.commit_count,
+                period.blob_count,
+                period.blob_size
+            ))
+            .collect::<Vec<(&str, u64, u64, u64)>>(),
+        vec![
+            ("2023-11", 2, 2, 400),
+            ("2023-12", 0, 0, 0),
+            ("2024-01", 0, 0, 0),
+            ("2024-02", 1, 1, 200),
+        ]
+    );
+}
+
+#[test]
+fn growth_renders_a_bar_chart() {
+    let growth = growth(&example_history(), Interval::Year);
+
+    assert_eq!(
+        growth.to_text(),
+        format!(
+            "2023 |{}| 400 B, 2 commits, 2 blobs\n\
+             2024 |{}{}| 200 B, 1 commit, 1 blob\n",
+            "#".repeat(40),
+            "#".repeat(20),
+            " ".repeat(20)
+        )
+    );
+}
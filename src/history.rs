@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::ref_selection::RefSelection;
+
+/// The mode Git uses for submodules, whose entries point to commits rather than blobs
+const SUBMODULE_MODE: &str = "160000";
+
+/// A file added or changed by a commit
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub path: String,
+    /// The object ID of the file's new contents
+    pub blob: String,
+}
+
+/// A commit, with the files it added or changed compared to its parents
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commit {
+    pub oid: String,
+    /// When the commit was made, as an ISO 8601 timestamp
+    pub date: String,
+    pub changes: Vec<Change>,
+}
+
+/// A repository's history, read with `git log`, for analysis which `git-sizer` doesn't
+/// provide, like which paths a blob appears at and when it was introduced
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    /// Every reachable commit, with parents before their children
+    pub commits: Vec<Commit>,
+    /// The size in bytes of every blob in the repository, keyed by object ID
+    pub blob_sizes: HashMap<String, u64>,
+}
+
+impl History {
+    /// Reads the history reachable from a repository's refs, or just the refs in a selection
+    pub fn read(path: &Path, ref_selection: &RefSelection) -> Result<History, Error> {
+        // Refs are passed on stdin, as there can be too many to pass as arguments
+        let revisions = if ref_selection.filters_refs() {
+            git_output(path, &["for-each-ref", "--format=%(refname)"], None)?
+                .lines()
+                .filter(|refname| ref_selection.matches(refname))
+                .map(|refname| format!("{}\n", refname))
+                .collect()
+        } else {
+            String::new()
+        };
+
+        let log_output = if ref_selection.filters_refs() && revisions.is_empty() {
+            String::new()
+        } else {
+            git_output(
+                path,
+                &[
+                    "log",
+                    if ref_selection.filters_refs() {
+                        "--stdin"
+                    } else {
+                        "--all"
+                    },
+                    "--topo-order",
+                    "--reverse",
+                    "--root",
+                    // Show the changes in merge commits against each parent, so blobs which first
+                    // appear in a merge are still found
+                    "-m",
+                    "--raw",
+                    "--no-abbrev",
+                    "--no-renames",
+                    "-z",
+                    "--format=%x01%H %cI",
+                ],
+                Some(&revisions),
+            )?
+        };
+
+        let blob_sizes_output = git_output(
+            path,
+            &[
+                "cat-file",
+                "--batch-all-objects",
+                "--batch-check=%(objectname) %(objecttype) %(objectsize)",
+            ],
+            None,
+        )?;
+
+        Ok(History {
+            commits: parse_log(&log_output)?,
+            blob_sizes: parse_blob_sizes(&blob_sizes_output)?,
+        })
+    }
+
+    /// The size of a blob in bytes, or 0 if it isn't in the repository
+    pub fn blob_size(&self, blob: &str) -> u64 {
+        self.blob_sizes.get(blob).copied().unwrap_or(0)
+    }
+}
+
+fn git_output(path: &Path, args: &[&str], input: Option<&str>) -> Result<String, Error> {
+    git_output_bytes(path, args, input).map(|output| String::from_utf8_lossy(&output).to_string())
+}
+
+/// Runs `git` in a repository, returning its raw output, e.g. for reading objects' contents
+pub(crate) fn git_output_bytes(
+    path: &Path,
+    args: &[&str],
+    input: Option<&str>,
+) -> Result<Vec<u8>, Error> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // The input is written on a separate thread while the output is read, as `git` may stop
+    // reading its input until its output has been read, e.g. `cat-file --batch`. Dropping stdin
+    // once the input is written closes it, so `git` knows the input is complete.
+    let stdin = child.stdin.take();
+    let input = input.unwrap_or("").to_string();
+    let writer = std::thread::spawn(move || -> Result<(), Error> {
+        if let Some(mut stdin) = stdin {
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        Ok(())
+    });
+
+    let output = child.wait_with_output()?;
+
+    writer
+        .join()
+        .map_err(|_| Error::other("Failed to write input to `git`"))??;
+
+    if !output.status.success() {
+        return Err(Error::other(format!(
+            "Failed to read the repository's history: {}",
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )));
+    }
+
+    Ok(output.stdout)
+}
+
+pub(crate) fn invalid_output(output: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Failed to parse output from `git`: {}", output),
+    )
+}
+
+/// Parses the output of `git log --raw -z --format=%x01%H %cI`, where each commit starts with
+/// a `\x01` and each change is a NUL-terminated `:` line followed by a NUL-terminated path
+fn parse_log(output: &str) -> Result<Vec<Commit>, Error> {
+    let mut commits: Vec<Commit> = Vec::new();
+    let mut fields = output
+        .split('\0')
+        .map(|field| field.trim_start_matches('\n'));
+
+    while let Some(field) = fields.next() {
+        if let Some(header) = field.strip_prefix('\x01') {
+            let (oid, date) = header
+                .split_once(' ')
+                .ok_or_else(|| invalid_output(field))?;
+
+            // With `-m`, merge commits are listed once for each parent
+            if commits.last().is_some_and(|commit| commit.oid == oid) {
+                continue;
+            }
+
+            commits.push(Commit {
+                oid: oid.to_string(),
+                date: date.to_string(),
+                changes: Vec::new(),
+            });
+        } else if let Some(change) = field.strip_prefix(':') {
+            let path = fields.next().ok_or_else(|| invalid_output(field))?;
+            let commit = commits.last_mut().ok_or_else(|| invalid_output(field))?;
+
+            // The fields are the old and new modes, the old and new object IDs and the status
+            let [_, new_mode, _, new_blob, status] = change
+                .split(' ')
+                .collect::<Vec<&str>>()
+                .try_into()
+                .map_err(|_| invalid_output(field))?;
+
+            if status == "D" || new_mode == SUBMODULE_MODE {
+                continue;
+            }
+
+            commit.changes.push(Change {
+                path: path.to_string(),
+                blob: new_blob.to_string(),
+            });
+        } else if !field.is_empty() {
+            return Err(invalid_output(field));
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Parses the output of `git cat-file --batch-check`, keeping only blobs
+fn parse_blob_sizes(output: &str) -> Result<HashMap<String, u64>, Error> {
+    let mut blob_sizes = HashMap::new();
+
+    for line in output.lines() {
+        let mut parts = line.split(' ');
+
+        if let (Some(oid), Some("blob"), Some(size)) = (parts.next(), parts.next(), parts.next()) {
+            let size = size.parse().map_err(|_| invalid_output(line))?;
+            blob_sizes.insert(oid.to_string(), size);
+        }
+    }
+
+    Ok(blob_sizes)
+}
+
+/// Creates a repository with a known history for testing, returning its path
+#[cfg(test)]
+pub fn example_repository(directory: &Path) -> std::path::PathBuf {
+    let path = directory.join("repository");
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&path)
+            .args([
+                "-c",
+                "user.name=Octocat",
+                "-c",
+                "user.email=octocat@github.com",
+            ])
+            .args(args)
+            .env("GIT_AUTHOR_DATE", "2024-01-15T12:00:00Z")
+            .env("GIT_COMMITTER_DATE", "2024-01-15T12:00:00Z")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+
+    std::fs::create_dir_all(path.join("assets")).unwrap();
+    git(&["init", "--quiet", "--initial-branch=main"]);
+
+    std::fs::write(path.join("README.md"), "# Example\n").unwrap();
+    std::fs::write(path.join("assets/logo.png"), vec![b'a'; 2000]).unwrap();
+    git(&["add", "--all"]);
+    git(&["commit", "--quiet", "--message", "Initial commit"]);
+
+    std::fs::write(path.join("assets/copy of logo.png"), vec![b'a'; 2000]).unwrap();
+    std::fs::write(path.join("assets/video.mp4"), vec![b'b'; 5000]).unwrap();
+    git(&["add", "--all"]);
+    git(&["commit", "--quiet", "--message", "Add video"]);
+
+    std::fs::remove_file(path.join("assets/video.mp4")).unwrap();
+    git(&["add", "--all"]);
+    git(&["commit", "--quiet", "--message", "Remove video"]);
+
+    path
+}
+
+#[test]
+fn parse_log_reads_commits_and_changes() {
+    let output = "\x01aaa 2024-01-01T00:00:00+00:00\0\n\
+                  :000000 100644 0000 b1 A\0README.md\0\
+                  :000000 160000 0000 c1 A\0vendor/lib\0\
+                  \x01bbb 2024-02-01T00:00:00+00:00\0\n\
+                  :100644 000000 b1 0000 D\0README.md\0\
+                  :000000 100644 0000 b2 A\0docs/a file.md\0\
+                  \x01ccc 2024-03-01T00:00:00+00:00\0\
+                  \x01ccc 2024-03-01T00:00:00+00:00\0\n\
+                  :100644 100644 b2 b3 M\0docs/a file.md\0";
+
+    assert_eq!(
+        parse_log(output).unwrap(),
+        vec![
+            Commit {
+                oid: "aaa".to_string(),
+                date: "2024-01-01T00:00:00+00:00".to_string(),
+                changes: vec![Change {
+                    path: "README.md".to_string(),
+                    blob: "b1".to_string(),
+                }],
+            },
+            Commit {
+                oid: "bbb".to_string(),
+                date: "2024-02-01T00:00:00+00:00".to_string(),
+                changes: vec![Change {
+                    path: "docs/a file.md".to_string(),
+                    blob: "b2".to_string(),
+                }],
+            },
+            Commit {
+                oid: "ccc".to_string(),
+                date: "2024-03-01T00:00:00+00:00".to_string(),
+                changes: vec![Change {
+                    path: "docs/a file.md".to_string(),
+                    blob: "b3".to_string(),
+                }],
+            },
+        ]
+    );
+}
+
+#[test]
+fn read_finds_every_blob_in_history() {
+    let directory = tempfile::tempdir().unwrap();
+    let history = History::read(
+        &example_repository(directory.path()),
+        &RefSelection::default(),
+    )
+    .unwrap();
+
+    assert_eq!(history.commits.len(), 3);
+    assert_eq!(
+        history.commits[1]
+            .changes
+            .iter()
+            .map(|change| change.path.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["assets/copy of logo.png", "assets/video.mp4"]
+    );
+    assert!(history.commits[2].changes.is_empty());
+    assert_eq!(history.blob_size(&history.commits[1].changes[1].blob), 5000);
+}
+
+#[test]
+fn read_only_follows_selected_refs() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = example_repository(directory.path());
+
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(&path)
+        .args(["branch", "first", "HEAD~2"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let history = History::read(
+        &path,
+        &RefSelection {
+            refs: vec!["refs/heads/first".to_string()],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(history.commits.len(), 1);
+
+    let history = History::read(
+        &path,
+        &RefSelection {
+            exclude_refs: vec!["refs/heads".to_string()],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert!(history.commits.is_empty());
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Error;
+use std::path::Path;
+
+use crate::history::{git_output_bytes, invalid_output, History};
+use crate::sizer_report::format_bytes;
+
+/// Git LFS never treats blobs bigger than this as pointer files, so larger blobs aren't read
+const MAX_POINTER_SIZE: u64 = 1024;
+
+/// The first line of every Git LFS pointer file
+const POINTER_VERSION_LINE: &str = "version https://git-lfs.github.com/spec/v1\n";
+
+/// The Git LFS objects referenced by pointer files in a repository's history. `git-sizer` only
+/// sees the small pointer files, not the objects themselves, which are stored separately.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LfsObjects {
+    /// The number of distinct Git LFS objects referenced
+    pub object_count: u64,
+    /// The total size of the objects, in bytes, as declared in their pointer files
+    pub total_size: u64,
+    /// The number of distinct pointer files in history
+    pub pointer_count: u64,
+}
+
+impl LfsObjects {
+    pub fn to_text(&self) -> String {
+        if self.pointer_count == 0 {
+            return "There are no Git LFS pointer files in the repository's history\n".to_string();
+        }
+
+        format!(
+            "{} objects totalling {}, referenced by {} pointer files\n",
+            self.object_count,
+            format_bytes(self.total_size),
+            self.pointer_count
+        )
+    }
+}
+
+/// Finds the Git LFS pointer files in a repository's history and sums the sizes they declare,
+/// without needing to download the objects themselves
+pub fn lfs_objects(path: &Path, history: &History) -> Result<LfsObjects, Error> {
+    let candidates: BTreeSet<&str> = history
+        .commits
+        .iter()
+        .flat_map(|commit| &commit.changes)
+        .map(|change| change.blob.as_str())
+        .filter(|blob| history.blob_size(blob) <= MAX_POINTER_SIZE)
+        .collect();
+
+    if candidates.is_empty() {
+        return Ok(LfsObjects::default());
+    }
+
+    let input: String = candidates
+        .iter()
+        .map(|blob| format!("{}\n", blob))
+        .collect();
+    let output = git_output_bytes(path, &["cat-file", "--batch"], Some(&input))?;
+
+    let mut objects: HashMap<String, u64> = HashMap::new();
+    let mut pointer_count = 0;
+
+    for contents in parse_batch(&output)? {
+        if let Some((oid, size)) = parse_pointer(contents) {
+            pointer_count += 1;
+            objects.insert(oid, size);
+        }
+    }
+
+    Ok(LfsObjects {
+        object_count: objects.len() as u64,
+        total_size: objects.values().sum(),
+        pointer_count,
+    })
+}
+
+/// Splits the output of `git cat-file --batch` into each object's contents. Each object is a
+/// `<oid> <type> <size>` header line, followed by its contents and a newline.
+fn parse_batch(output: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    let mut objects = Vec::new();
+    let mut rest = output;
+
+    while !rest.is_empty() {
+        let header_end = rest
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or_else(|| invalid_output(&String::from_utf8_lossy(rest)))?;
+        let header = String::from_utf8_lossy(&rest[..header_end]);
+        let size: usize = header
+            .rsplit(' ')
+            .next()
+            .and_then(|size| size.parse().ok())
+            .ok_or_else(|| invalid_output(&header))?;
+
+        let contents_start = header_end + 1;
+        let contents_end = contents_start + size;
+
+        if rest.len() < contents_end {
+            return Err(invalid_output(&header));
+        }
+
+        objects.push(&rest[contents_start..contents_end]);
+        rest = rest.get(contents_end + 1..).unwrap_or_default();
+    }
+
+    Ok(objects)
+}
+
+/// Reads the object ID and size from a Git LFS pointer file, or returns `None` if the contents
+/// aren't a pointer file
+fn parse_pointer(contents: &[u8]) -> Option<(String, u64)> {
+    let contents = std::str::from_utf8(contents).ok()?;
+    let contents = contents.strip_prefix(POINTER_VERSION_LINE)?;
+
+    let mut oid = None;
+    let mut size = None;
+
+    for line in contents.lines() {
+        if let Some(value) = line.strip_prefix("oid ") {
+            oid = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.parse().ok();
+        }
+    }
+
+    Some((oid?, size?))
+}
+
+#[cfg(test)]
+fn pointer(oid_character: char, size: u64) -> String {
+    format!(
+        "{}oid sha256:{}\nsize {}\n",
+        POINTER_VERSION_LINE,
+        oid_character.to_string().repeat(64),
+        size
+    )
+}
+
+#[test]
+fn parse_pointer_reads_the_oid_and_size() {
+    assert_eq!(
+        parse_pointer(pointer('a', 12345).as_bytes()),
+        Some((format!("sha256:{}", "a".repeat(64)), 12345))
+    );
+    assert_eq!(parse_pointer(b"# Example\n"), None);
+    assert_eq!(parse_pointer(POINTER_VERSION_LINE.as_bytes()), None);
+}
+
+#[test]
+fn parse_batch_splits_objects_including_binary_contents() {
+    let mut output = b"b1 blob 3\n\xff\n\x00\n".to_vec();
+    output.extend_from_slice(b"b2 blob 10\n# Example\n\n");
+
+    assert_eq!(
+        parse_batch(&output).unwrap(),
+        vec![&b"\xff\n\x00"[..], &b"# Example\n"[..]]
+    );
+    assert!(parse_batch(b"b1 blob 30\nshort\n").is_err());
+}
+
+#[test]
+fn lfs_objects_sums_the_sizes_declared_by_pointer_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = crate::history::example_repository(directory.path());
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&path)
+            .args([
+                "-c",
+                "user.name=Octocat",
+                "-c",
+                "user.email=octocat@github.com",
+            ])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+
+    std::fs::write(path.join("assets/design.psd"), pointer('a', 3_000_000)).unwrap();
+    std::fs::write(path.join("assets/intro.mov"), pointer('b', 2_000_000)).unwrap();
+    git(&["add", "--all"]);
+    git(&["commit", "--quiet", "--message", "Add LFS files"]);
+
+    std::fs::write(
+        path.join("assets/copy of intro.mov"),
+        pointer('b', 2_000_000),
+    )
+    .unwrap();
+    git(&["add", "--all"]);
+    git(&["commit", "--quiet", "--message", "Copy LFS file"]);
+
+    let history = History::read(&path, &crate::ref_selection::RefSelection::default()).unwrap();
+    let lfs_objects = lfs_objects(&path, &history).unwrap();
+
+    assert_eq!(
+        lfs_objects,
+        LfsObjects {
+            object_count: 2,
+            total_size: 5_000_000,
+            pointer_count: 2,
+        }
+    );
+    assert_eq!(
+        lfs_objects.to_text(),
+        "2 objects totalling 4.77 MiB, referenced by 2 pointer files\n"
+    );
+}
+
+#[test]
+fn lfs_objects_reads_many_small_blobs() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = crate::history::example_repository(directory.path());
+
+    std::fs::create_dir_all(path.join("small")).unwrap();
+
+    for index in 0..3000 {
+        std::fs::write(
+            path.join(format!("small/{}.txt", index)),
+            format!("{:0>200}\n", index),
+        )
+        .unwrap();
+    }
+
+    std::fs::write(path.join("small/design.psd"), pointer('a', 3_000_000)).unwrap();
+
+    for args in [
+        vec!["add", "--all"],
+        vec!["commit", "--quiet", "--message", "Add small files"],
+    ] {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&path)
+            .args([
+                "-c",
+                "user.name=Octocat",
+                "-c",
+                "user.email=octocat@github.com",
+            ])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    let history = History::read(&path, &crate::ref_selection::RefSelection::default()).unwrap();
+
+    assert_eq!(
+        lfs_objects(&path, &history).unwrap(),
+        LfsObjects {
+            object_count: 1,
+            total_size: 3_000_000,
+            pointer_count: 1,
+        }
+    );
+}
@@ -3,27 +3,250 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub mod enums;
 pub use crate::enums::OutputFormat;
 
+pub mod gh;
+
 pub mod github_repository_lister;
 pub use crate::github_repository_lister::GitHubRepositoryLister;
 
+pub mod enterprise;
+
+pub mod estimate;
+
+pub mod sizer_report;
+pub use crate::sizer_report::SizerReport;
+
+pub mod policy;
+pub use crate::policy::Policy;
+
+pub mod migration_readiness;
+
+pub mod ref_selection;
+
+pub mod additional_refs;
+
+pub mod history;
+
+pub mod top_blobs;
+
+pub mod lfs;
+
+pub mod recommendations;
+
+pub mod breakdown;
+
+pub mod growth;
+
+pub mod report;
+pub use crate::report::Report;
+
+pub mod clone_cache;
+
+pub mod name_pattern;
+
+pub mod repository_filter;
+
+pub mod repository_source;
+
+pub mod repository_sizer;
+pub use crate::repository_sizer::RepositorySizer;
+
+pub mod wiki;
+
+pub mod org;
+
+pub mod gists;
+
+pub mod scan;
+
+pub mod aggregate;
+
 pub mod generate_script {
     use crate::enums::OutputFormat;
     use crate::enums::ScriptType;
-    use crate::github_repository_lister::GitHubRepositoryLister;
+    use crate::enums::SortBy;
+    use crate::github_repository_lister::{GitHubRepositoryLister, Repository};
+    use crate::repository_filter::{self, RepositoryFilter};
+    use crate::repository_source::RepositorySource;
+    use crate::sizer_report::format_bytes;
+    use crate::wiki::wiki_nwo;
     use path_slash::PathBufExt as _;
-    use std::io::{Error, Write};
+    use std::collections::HashMap;
+    use std::io::{Error, ErrorKind, Write};
     use std::path::PathBuf;
 
     #[cfg(test)]
     use crate::github_repository_lister::MockGitHubRepositoryLister;
 
+    /// Builds the filename for a repository's output from a template containing the `${owner}` and `${repository}` placeholders
+    pub fn output_filename(template: &str, owner: &str, repository_name: &str) -> String {
+        template
+            .replace("${owner}", owner)
+            .replace("${repository}", repository_name)
+    }
+
+    /// The inverse of `output_filename`: if a filename matches a template, returns the owner
+    /// (if the template includes `${owner}`) and repository name it was generated from
+    pub fn parse_output_filename(
+        template: &str,
+        filename: &str,
+    ) -> Option<(Option<String>, String)> {
+        let mut pattern = String::from("^");
+        let mut remaining = template;
+        let mut seen_owner = false;
+        let mut seen_repository = false;
+
+        while let Some(index) = remaining.find("${") {
+            pattern.push_str(&regex::escape(&remaining[..index]));
+            remaining = &remaining[index..];
+
+            if let Some(rest) = remaining.strip_prefix("${owner}") {
+                pattern.push_str(if seen_owner {
+                    "(?:.+?)"
+                } else {
+                    "(?P<owner>.+?)"
+                });
+                seen_owner = true;
+                remaining = rest;
+            } else if let Some(rest) = remaining.strip_prefix("${repository}") {
+                pattern.push_str(if seen_repository {
+                    "(?:.+?)"
+                } else {
+                    "(?P<repository>.+?)"
+                });
+                seen_repository = true;
+                remaining = rest;
+            } else {
+                pattern.push_str(&regex::escape("${"));
+                remaining = &remaining[2..];
+            }
+        }
+
+        pattern.push_str(&regex::escape(remaining));
+        pattern.push('$');
+
+        let captures = regex::Regex::new(&pattern).ok()?.captures(filename)?;
+
+        Some((
+            captures
+                .name("owner")
+                .map(|owner| owner.as_str().to_string()),
+            captures.name("repository")?.as_str().to_string(),
+        ))
+    }
+
+    /// Formats extra arguments for the `repo` command so they can be appended to a line of a
+    /// generated script, quoting any which contain characters special to the shell
+    fn quote_arguments(arguments: &[String], script_type: ScriptType) -> String {
+        arguments
+            .iter()
+            .map(|argument| {
+                let is_safe = !argument.is_empty()
+                    && argument.chars().all(|character| {
+                        character.is_ascii_alphanumeric() || "-_./:=@,+".contains(character)
+                    });
+
+                if is_safe {
+                    format!(" {}", argument)
+                } else if matches!(script_type, ScriptType::Bash) {
+                    format!(" '{}'", argument.replace('\'', "'\\''"))
+                } else {
+                    format!(" '{}'", argument.replace('\'', "''"))
+                }
+            })
+            .collect()
+    }
+
+    /// Checks that every target's output would be saved to a different path, so no target's
+    /// results would be overwritten by another's, e.g. when several owners have a repository
+    /// with the same name. Each output is the target's name and the path to save it to.
+    pub fn ensure_unique_output_paths(outputs: &[(String, PathBuf)]) -> Result<(), Error> {
+        let mut names_by_path: HashMap<&PathBuf, &str> = HashMap::new();
+
+        for (name, path) in outputs {
+            if let Some(existing_name) = names_by_path.insert(path, name) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "The results for {} and {} would both be saved to {}, so one would overwrite the other. Use an output filename which includes `${{owner}}`, e.g. `${{owner}}-${{repository}}.txt`, so each is saved to a different file.",
+                        existing_name,
+                        name,
+                        path.display()
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The path to save a repository's output to, in the output directory
+    fn output_path(
+        output_directory: &str,
+        output_filename_template: &str,
+        repository: &Repository,
+    ) -> PathBuf {
+        PathBuf::from(output_filename(
+            output_directory,
+            &repository.owner,
+            &repository.name,
+        ))
+        .join(output_filename(
+            output_filename_template,
+            &repository.owner,
+            &repository.name,
+        ))
+    }
+
+    /// The directories which a script needs to create for its output. The output directory can
+    /// include the `${owner}` placeholder, for example to save each owner's results separately.
+    fn output_directories(output_directory: &str, repositories: &[Repository]) -> Vec<String> {
+        if !output_directory.contains("${") {
+            return vec![output_directory.to_string()];
+        }
+
+        let mut output_directories: Vec<String> = Vec::new();
+
+        for repository in repositories {
+            let repository_output_directory =
+                output_filename(output_directory, &repository.owner, &repository.name);
+
+            if !output_directories.contains(&repository_output_directory) {
+                output_directories.push(repository_output_directory);
+            }
+        }
+
+        output_directories
+    }
+
+    /// Describes a repository in the progress line printed before it is sized, including its
+    /// size reported by the API, if known, so the person running the script knows what's coming
+    fn progress_description(repository: &Repository, index: usize, repos_count: usize) -> String {
+        match repository.size() {
+            Some(size) => format!(
+                "{} ({}/{}, {})",
+                repository.nwo(),
+                index + 1,
+                repos_count,
+                format_bytes(size)
+            ),
+            None => format!("{} ({}/{})", repository.nwo(), index + 1, repos_count),
+        }
+    }
+
+    /// Builds the filename for a repository's wiki output, by adding `.wiki` to the repository's
+    /// name, e.g. `${repository}.txt` becomes `gh-sizer.wiki.txt`
+    fn wiki_output_filename(template: &str, repository: &Repository) -> String {
+        output_filename(template, &repository.owner, &wiki_nwo(&repository.name))
+    }
+
     fn generate_powershell_script(
-        repository_names: Vec<String>,
-        owner: &str,
+        repositories: &[Repository],
         output_format: OutputFormat,
         output_directory: &str,
         output_filename_template: &str,
         gh_sizer_command: &str,
+        repo_arguments: &[String],
+        include_wiki: bool,
     ) -> String {
         let mut generated_script: String = format!(
             "#!/usr/bin/env pwsh\n\n# Script generated by gh-sizer v{}\n\n",
@@ -31,34 +254,55 @@ pub mod generate_script {
         );
 
         // Update line to create a directory
-        let script_line = format!(
-            "New-Item -Name {} -ItemType directory -Force | Out-Null\n",
-            output_directory
-        )
-        .to_string();
-        generated_script.push_str(&script_line);
+        for output_directory in output_directories(output_directory, repositories) {
+            let script_line = format!(
+                "New-Item -Name {} -ItemType directory -Force | Out-Null\n",
+                output_directory
+            )
+            .to_string();
+            generated_script.push_str(&script_line);
+        }
 
-        let repos_count = repository_names.len();
+        let repos_count = repositories.len();
 
-        for (index, repository_name) in repository_names.iter().enumerate() {
-            let output_filename = output_filename_template
-                .replace("${owner}", owner)
-                .replace("${repository}", repository_name);
-            let output_path = PathBuf::from(output_directory).join(output_filename);
+        for (index, repository) in repositories.iter().enumerate() {
+            let output_path = output_path(output_directory, output_filename_template, repository);
             let script_lines = format!(
-                "Write-Output \"Processing repo {}/{} ({}/{})\"\n{} repo {}/{} --output-format {} | Out-File -Path {}\n",
-                owner,
-                repository_name,
-                index + 1,
-                repos_count,
+                "Write-Output \"Processing repo {}\"\n{} repo {}/{} --output-format {}{} | Out-File -Path {}\n",
+                progress_description(repository, index, repos_count),
                 gh_sizer_command,
-                owner,
-                repository_name,
+                repository.owner,
+                repository.name,
                 output_format,
+                quote_arguments(repo_arguments, ScriptType::Powershell),
                 output_path.display()
             )
             .to_string();
             generated_script.push_str(&script_lines);
+
+            if include_wiki {
+                let wiki_output_path = PathBuf::from(output_filename(
+                    output_directory,
+                    &repository.owner,
+                    &repository.name,
+                ))
+                .join(wiki_output_filename(output_filename_template, repository));
+
+                // A repo without a wiki produces no output, so remove the file. Windows
+                // PowerShell's `Out-File` writes a byte order mark even without any input, so
+                // check the file's content rather than its length.
+                generated_script.push_str(&format!(
+                    "Write-Output \"Processing wiki for repo {}\"\n{} repo {} --output-format {}{} | Out-File -Path {}\nif (-not (Get-Content {})) {{ Remove-Item {} }}\n",
+                    repository.nwo(),
+                    gh_sizer_command,
+                    wiki_nwo(&repository.nwo()),
+                    output_format,
+                    quote_arguments(repo_arguments, ScriptType::Powershell),
+                    wiki_output_path.display(),
+                    wiki_output_path.display(),
+                    wiki_output_path.display()
+                ));
+            }
         }
 
         generated_script.push_str(&format!(
@@ -66,47 +310,68 @@ pub mod generate_script {
             crate::VERSION
         ));
 
-        return generated_script;
+        generated_script
     }
 
     fn generate_bash_script(
-        repository_names: Vec<String>,
-        owner: &str,
+        repositories: &[Repository],
         output_format: OutputFormat,
         output_directory: &str,
         output_filename_template: &str,
         gh_sizer_command: &str,
+        repo_arguments: &[String],
+        include_wiki: bool,
     ) -> String {
         let mut generated_script: String = format!(
             "#!/usr/bin/env bash\n\n# Script generated by gh-sizer v{}\n\n",
             crate::VERSION
         );
 
-        let script_line = format!("mkdir -p {}\n", output_directory).to_string();
-        generated_script.push_str(&script_line);
+        for output_directory in output_directories(output_directory, repositories) {
+            let script_line = format!("mkdir -p {}\n", output_directory).to_string();
+            generated_script.push_str(&script_line);
+        }
 
-        let repos_count = repository_names.len();
+        let repos_count = repositories.len();
 
-        for (index, repository_name) in repository_names.iter().enumerate() {
-            let output_filename = output_filename_template
-                .replace("${owner}", owner)
-                .replace("${repository}", repository_name);
-            let output_path = PathBuf::from(output_directory).join(output_filename);
+        for (index, repository) in repositories.iter().enumerate() {
+            let output_path = output_path(output_directory, output_filename_template, repository);
             let output_path_for_bash = output_path.to_slash().unwrap();
             let script_lines = format!(
-                "echo \"Processing repo {}/{} ({}/{})\"\n{} repo {}/{} --output-format {} > {}\n",
-                owner,
-                repository_name,
-                index + 1,
-                repos_count,
+                "echo \"Processing repo {}\"\n{} repo {}/{} --output-format {}{} > {}\n",
+                progress_description(repository, index, repos_count),
                 gh_sizer_command,
-                owner,
-                repository_name,
+                repository.owner,
+                repository.name,
                 output_format,
+                quote_arguments(repo_arguments, ScriptType::Bash),
                 output_path_for_bash
             )
             .to_string();
             generated_script.push_str(&script_lines);
+
+            if include_wiki {
+                let wiki_output_path = PathBuf::from(output_filename(
+                    output_directory,
+                    &repository.owner,
+                    &repository.name,
+                ))
+                .join(wiki_output_filename(output_filename_template, repository));
+                let wiki_output_path_for_bash = wiki_output_path.to_slash().unwrap();
+
+                // A repo without a wiki produces no output, so remove the empty file
+                generated_script.push_str(&format!(
+                    "echo \"Processing wiki for repo {}\"\n{} repo {} --output-format {}{} > {}\n[ -s {} ] || rm -f {}\n",
+                    repository.nwo(),
+                    gh_sizer_command,
+                    wiki_nwo(&repository.nwo()),
+                    output_format,
+                    quote_arguments(repo_arguments, ScriptType::Bash),
+                    wiki_output_path_for_bash,
+                    wiki_output_path_for_bash,
+                    wiki_output_path_for_bash
+                ));
+            }
         }
 
         generated_script.push_str(&format!(
@@ -114,49 +379,83 @@ pub mod generate_script {
             crate::VERSION
         ));
 
-        return generated_script;
+        generated_script
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn call(
-        owner: &str,
+        repository_source: &RepositorySource,
         script_type: ScriptType,
         output_format: OutputFormat,
         output_directory: &str,
         output_filename_template: &str,
         gh_sizer_command: &str,
+        repo_arguments: &[String],
+        repository_filter: &RepositoryFilter,
+        sort_by: Option<SortBy>,
+        reverse: bool,
+        include_wiki: bool,
         github_repository_lister: &impl GitHubRepositoryLister,
         stderr: &mut impl Write,
     ) -> Result<String, Error> {
-        let repository_names = github_repository_lister.call(owner)?;
+        let mut repositories =
+            repository_source.list(repository_filter, github_repository_lister, stderr)?;
+
+        if let Some(sort_by) = sort_by {
+            repository_filter::sort(&mut repositories, sort_by, reverse);
+        }
+
+        let mut outputs: Vec<(String, PathBuf)> = Vec::new();
+
+        for repository in &repositories {
+            outputs.push((
+                repository.nwo(),
+                output_path(output_directory, output_filename_template, repository),
+            ));
+
+            if include_wiki {
+                outputs.push((
+                    wiki_nwo(&repository.nwo()),
+                    PathBuf::from(output_filename(
+                        output_directory,
+                        &repository.owner,
+                        &repository.name,
+                    ))
+                    .join(wiki_output_filename(output_filename_template, repository)),
+                ));
+            }
+        }
+
+        ensure_unique_output_paths(&outputs)?;
 
         writeln!(
             stderr,
-            "Generating script for {} repositories owned by {}",
-            repository_names.len(),
-            owner
+            "Generating script for {} repositories {}",
+            repositories.len(),
+            repository_source
         )?;
 
-        let generated_script;
-
-        if matches!(script_type, ScriptType::Bash) {
-            generated_script = generate_bash_script(
-                repository_names,
-                owner,
+        let generated_script = if matches!(script_type, ScriptType::Bash) {
+            generate_bash_script(
+                &repositories,
                 output_format,
                 output_directory,
                 output_filename_template,
                 gh_sizer_command,
-            );
+                repo_arguments,
+                include_wiki,
+            )
         } else {
-            generated_script = generate_powershell_script(
-                repository_names,
-                owner,
+            generate_powershell_script(
+                &repositories,
                 output_format,
                 output_directory,
                 output_filename_template,
                 gh_sizer_command,
-            );
-        }
+                repo_arguments,
+                include_wiki,
+            )
+        };
 
         Ok(generated_script)
     }
@@ -168,17 +467,22 @@ pub mod generate_script {
 
         lister_mock
             .expect_call()
-            .returning(|_| Ok(vec!["gh-sizer".to_string()]));
+            .returning(|_| Ok(vec![Repository::new("github", "gh-sizer")]));
 
         let mut stderr = Vec::new();
 
         let bash_script = call(
-            "github",
+            &RepositorySource::Owners(vec!["github".to_string()]),
             ScriptType::Bash.to_owned(),
             OutputFormat::Text.to_owned(),
             "output/directory",
             "${owner}-${repository}.txt",
             "gh sizer",
+            &[],
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
             &lister_mock,
             &mut stderr,
         )
@@ -194,17 +498,22 @@ pub mod generate_script {
 
         lister_mock
             .expect_call()
-            .returning(|_| Ok(vec!["gh-sizer".to_string()]));
+            .returning(|_| Ok(vec![Repository::new("github", "gh-sizer")]));
 
         let mut stderr = Vec::new();
 
         let powershell_script = call(
-            "github",
+            &RepositorySource::Owners(vec!["github".to_string()]),
             ScriptType::Powershell.to_owned(),
             OutputFormat::Text.to_owned(),
             "output/directory",
             "${owner}-${repository}.txt",
             "gh sizer",
+            &[],
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
             &lister_mock,
             &mut stderr,
         )
@@ -220,17 +529,22 @@ pub mod generate_script {
 
         lister_mock
             .expect_call()
-            .returning(|_| Ok(vec!["gh-sizer".to_string()]));
+            .returning(|_| Ok(vec![Repository::new("github", "gh-sizer")]));
 
         let mut stderr = Vec::new();
 
         let bash_script = call(
-            "github",
+            &RepositorySource::Owners(vec!["github".to_string()]),
             ScriptType::Bash.to_owned(),
             OutputFormat::Json.to_owned(),
             "output/directory",
             "${repository}.txt",
             "gh sizer",
+            &[],
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
             &lister_mock,
             &mut stderr,
         )
@@ -246,17 +560,22 @@ pub mod generate_script {
 
         lister_mock
             .expect_call()
-            .returning(|_| Ok(vec!["gh-sizer".to_string()]));
+            .returning(|_| Ok(vec![Repository::new("github", "gh-sizer")]));
 
         let mut stderr = Vec::new();
 
         let powershell_script = call(
-            "github",
+            &RepositorySource::Owners(vec!["github".to_string()]),
             ScriptType::Powershell.to_owned(),
             OutputFormat::Json.to_owned(),
             "output/directory",
             "${repository}.txt",
             "gh sizer",
+            &[],
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
             &lister_mock,
             &mut stderr,
         )
@@ -265,23 +584,363 @@ pub mod generate_script {
         insta::assert_yaml_snapshot!(powershell_script);
     }
 
+    #[test]
+    fn generate_script_quotes_repo_arguments() {
+        let mut lister_mock = MockGitHubRepositoryLister::new();
+
+        lister_mock
+            .expect_call()
+            .returning(|_| Ok(vec![Repository::new("github", "gh-sizer")]));
+
+        let repo_arguments = vec![
+            "--cache-dir".to_string(),
+            "/var/cache/it's here".to_string(),
+        ];
+
+        let bash_script = call(
+            &RepositorySource::Owners(vec!["github".to_string()]),
+            ScriptType::Bash,
+            OutputFormat::Text,
+            "output",
+            "${repository}.txt",
+            "gh sizer",
+            &repo_arguments,
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
+            &lister_mock,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert!(bash_script.contains(
+            "gh sizer repo github/gh-sizer --output-format text --cache-dir '/var/cache/it'\\''s here' > "
+        ));
+
+        let powershell_script = call(
+            &RepositorySource::Owners(vec!["github".to_string()]),
+            ScriptType::Powershell,
+            OutputFormat::Text,
+            "output",
+            "${repository}.txt",
+            "gh sizer",
+            &repo_arguments,
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
+            &lister_mock,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert!(powershell_script.contains(
+            "gh sizer repo github/gh-sizer --output-format text --cache-dir '/var/cache/it''s here' | Out-File"
+        ));
+    }
+
+    #[test]
+    fn generate_script_applies_repository_filter() {
+        let mut lister_mock = MockGitHubRepositoryLister::new();
+
+        lister_mock.expect_call().returning(|_| {
+            Ok(vec![
+                Repository::new("github", "gh-sizer"),
+                Repository {
+                    owner: "github".to_string(),
+                    name: "forked-repo".to_string(),
+                    is_fork: true,
+                    ..Default::default()
+                },
+            ])
+        });
+
+        let mut stderr = Vec::new();
+
+        let bash_script = call(
+            &RepositorySource::Owners(vec!["github".to_string()]),
+            ScriptType::Bash,
+            OutputFormat::Text,
+            "output",
+            "${repository}.txt",
+            "gh sizer",
+            &[],
+            &RepositoryFilter {
+                exclude_forks: true,
+                ..Default::default()
+            },
+            None,
+            false,
+            false,
+            &lister_mock,
+            &mut stderr,
+        )
+        .unwrap();
+
+        assert!(bash_script.contains("gh sizer repo github/gh-sizer "));
+        assert!(!bash_script.contains("forked-repo"));
+        assert_eq!(
+            String::from_utf8_lossy(&stderr),
+            "Excluded 1 of 2 repositories owned by github using filters\nGenerating script for 1 repositories owned by github\n"
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn generate_script_sorts_by_size_and_shows_size_in_progress_lines() {
+        let mut lister_mock = MockGitHubRepositoryLister::new();
+
+        lister_mock.expect_call().returning(|owner| {
+            Ok(vec![
+                Repository {
+                    disk_usage: Some(2048),
+                    ..Repository::new(owner, "small")
+                },
+                Repository {
+                    disk_usage: Some(3 * 1024 * 1024),
+                    ..Repository::new(owner, "large")
+                },
+                Repository {
+                    disk_usage: Some(20 * 1024 * 1024),
+                    ..Repository::new(owner, "too-large")
+                },
+            ])
+        });
+
+        let mut stderr = Vec::new();
+
+        let bash_script = call(
+            &RepositorySource::Owners(vec!["github".to_string()]),
+            ScriptType::Bash,
+            OutputFormat::Text,
+            "output",
+            "${repository}.txt",
+            "gh sizer",
+            &[],
+            &RepositoryFilter {
+                max_size: Some(10 * 1024 * 1024 * 1024),
+                ..Default::default()
+            },
+            Some(SortBy::Size),
+            false,
+            false,
+            &lister_mock,
+            &mut stderr,
+        )
+        .unwrap();
+
+        assert!(bash_script.contains(
+            "echo \"Processing repo github/large (1/2, 3.00 GiB)\"\n\
+             gh sizer repo github/large --output-format text > output/large.txt\n\
+             echo \"Processing repo github/small (2/2, 2.00 MiB)\"\n"
+        ));
+        assert!(!bash_script.contains("too-large"));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn generate_script_sizes_wikis_into_separate_files() {
+        let mut lister_mock = MockGitHubRepositoryLister::new();
+
+        lister_mock
+            .expect_call()
+            .returning(|_| Ok(vec![Repository::new("github", "gh-sizer")]));
+
+        let generate = |script_type: ScriptType| {
+            call(
+                &RepositorySource::Owners(vec!["github".to_string()]),
+                script_type,
+                OutputFormat::Json,
+                "output",
+                "${owner}-${repository}.json",
+                "gh sizer",
+                &[],
+                &RepositoryFilter::default(),
+                None,
+                false,
+                true,
+                &lister_mock,
+                &mut Vec::new(),
+            )
+            .unwrap()
+        };
+
+        assert!(generate(ScriptType::Bash).contains(
+            "gh sizer repo github/gh-sizer --output-format json > output/github-gh-sizer.json\n\
+             echo \"Processing wiki for repo github/gh-sizer\"\n\
+             gh sizer repo github/gh-sizer.wiki --output-format json > output/github-gh-sizer.wiki.json\n\
+             [ -s output/github-gh-sizer.wiki.json ] || rm -f output/github-gh-sizer.wiki.json\n"
+        ));
+        insta::assert_yaml_snapshot!(generate(ScriptType::Powershell));
+    }
+
+    #[test]
+    fn generate_script_sizes_repositories_from_repos_file_across_owners() {
+        let mut repos_file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(
+            &mut repos_file,
+            b"github/gh-sizer\nhttps://github.com/octocat/hello-world\n",
+        )
+        .unwrap();
+        let repos_file_path = repos_file.path().to_str().unwrap().to_string();
+
+        let mut stderr = Vec::new();
+
+        let bash_script = call(
+            &RepositorySource::ReposFile {
+                path: repos_file_path.clone(),
+                hostname: None,
+            },
+            ScriptType::Bash,
+            OutputFormat::Json,
+            "output",
+            "${owner}-${repository}.json",
+            "gh sizer",
+            &[],
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
+            &MockGitHubRepositoryLister::new(),
+            &mut stderr,
+        )
+        .unwrap();
+
+        assert!(bash_script.contains(
+            "echo \"Processing repo github/gh-sizer (1/2)\"\ngh sizer repo github/gh-sizer --output-format json > output/github-gh-sizer.json\n"
+        ));
+        assert!(bash_script.contains(
+            "echo \"Processing repo octocat/hello-world (2/2)\"\ngh sizer repo octocat/hello-world --output-format json > output/octocat-hello-world.json\n"
+        ));
+        assert_eq!(
+            String::from_utf8_lossy(&stderr),
+            format!(
+                "Generating script for 2 repositories listed in {}\n",
+                repos_file_path
+            )
+        );
+    }
+
+    #[test]
+    fn generate_script_covers_multiple_owners_with_per_owner_directories() {
+        let mut lister_mock = MockGitHubRepositoryLister::new();
+
+        lister_mock.expect_call().returning(|owner| {
+            Ok(vec![
+                Repository::new(owner, "first-repo"),
+                Repository::new(owner, "second-repo"),
+            ])
+        });
+
+        let mut stderr = Vec::new();
+
+        let bash_script = call(
+            &RepositorySource::Owners(vec!["org-a".to_string(), "org-b".to_string()]),
+            ScriptType::Bash,
+            OutputFormat::Text,
+            "output/${owner}",
+            "${repository}.txt",
+            "gh sizer",
+            &[],
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
+            &lister_mock,
+            &mut stderr,
+        )
+        .unwrap();
+
+        assert!(bash_script.contains("mkdir -p output/org-a\nmkdir -p output/org-b\n"));
+        assert!(bash_script.contains(
+            "echo \"Processing repo org-a/first-repo (1/4)\"\ngh sizer repo org-a/first-repo --output-format text > output/org-a/first-repo.txt\n"
+        ));
+        assert!(bash_script.contains(
+            "echo \"Processing repo org-b/second-repo (4/4)\"\ngh sizer repo org-b/second-repo --output-format text > output/org-b/second-repo.txt\n"
+        ));
+        assert_eq!(
+            String::from_utf8_lossy(&stderr),
+            "Generating script for 4 repositories owned by org-a and org-b\n"
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn generate_script_rejects_repos_from_different_owners_saved_to_the_same_file() {
+        let mut lister_mock = MockGitHubRepositoryLister::new();
+
+        lister_mock
+            .expect_call()
+            .returning(|owner| Ok(vec![Repository::new(owner, "api")]));
+
+        let generate = |output_filename_template: &str| {
+            call(
+                &RepositorySource::Owners(vec!["org-a".to_string(), "org-b".to_string()]),
+                ScriptType::Bash,
+                OutputFormat::Text,
+                "output",
+                output_filename_template,
+                "gh sizer",
+                &[],
+                &RepositoryFilter::default(),
+                None,
+                false,
+                false,
+                &lister_mock,
+                &mut Vec::new(),
+            )
+        };
+
+        let error = generate("${repository}.txt").unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(error.to_string().starts_with(
+            "The results for org-a/api and org-b/api would both be saved to output/api.txt"
+        ));
+
+        let bash_script = generate("${owner}-${repository}.txt").unwrap();
+
+        assert!(bash_script.contains("> output/org-a-api.txt\n"));
+        assert!(bash_script.contains("> output/org-b-api.txt\n"));
+    }
+
+    #[test]
+    fn parse_output_filename_extracts_owner_and_repository() {
+        assert_eq!(
+            parse_output_filename("${owner}-${repository}.txt", "github-gh-sizer.txt"),
+            Some((Some("github".to_string()), "gh-sizer".to_string()))
+        );
+        assert_eq!(
+            parse_output_filename("${repository}.json", "gh-sizer.json"),
+            Some((None, "gh-sizer".to_string()))
+        );
+        assert_eq!(
+            parse_output_filename("${repository}.json", "gh-sizer.txt"),
+            None
+        );
+    }
+
     #[test]
     fn generate_script_logs_number_of_repos() {
         let mut lister_mock = MockGitHubRepositoryLister::new();
 
         lister_mock
             .expect_call()
-            .returning(|_| Ok(vec!["gh-sizer".to_string()]));
+            .returning(|_| Ok(vec![Repository::new("github", "gh-sizer")]));
 
         let mut stderr = Vec::new();
 
         call(
-            "github",
+            &RepositorySource::Owners(vec!["github".to_string()]),
             ScriptType::Bash.to_owned(),
             OutputFormat::Json.to_owned(),
             "output/directory",
             "${repository}.txt",
             "gh sizer",
+            &[],
+            &RepositoryFilter::default(),
+            None,
+            false,
+            false,
             &lister_mock,
             &mut stderr,
         )
@@ -269,3 +269,133 @@ fn repo_command_outputs_repo_size_in_json_to_stdout() -> Result<(), Box<dyn std:
 
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "linux_integration_tests")]
+fn org_command_errors_without_gh() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("org")
+        .arg("gh-sizer-sandbox")
+        .arg("--gh-command")
+        .arg("noop");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "`gh` not found. To use gh-sizer, please install the GitHub CLI (https://cli.github.com)",
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "linux_integration_tests")]
+fn repo_command_errors_with_missing_policy_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("repo")
+        .arg("gh-sizer-sandbox/first-repo")
+        .arg("--policy")
+        .arg("does-not-exist.toml");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Failed to read policy file does-not-exist.toml",
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "linux_integration_tests")]
+fn local_command_errors_when_path_is_not_a_repo() -> Result<(), Box<dyn std::error::Error>> {
+    let directory = tempfile::tempdir()?;
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("local").arg(directory.path());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a Git repository"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "linux_integration_tests")]
+fn generate_script_command_sizes_repos_across_an_enterprise(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let directory = tempfile::tempdir()?;
+    let fake_gh = directory.path().join("gh");
+
+    // A fake `gh` which returns canned responses for listing an enterprise's organizations and
+    // each organization's repos
+    fs::write(
+        &fake_gh,
+        r#"#!/bin/sh
+case "$1" in
+  api)
+    echo '{"data":{"enterprise":{"organizations":{"nodes":[{"login":"org-a"},{"login":"org-b"}],"pageInfo":{"hasNextPage":false,"endCursor":null}}}}}'
+    ;;
+  repo)
+    echo "[{\"name\":\"$3-api\"}]"
+    ;;
+esac
+"#,
+    )?;
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755))?;
+
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("generate-script")
+        .arg("--enterprise")
+        .arg("acme")
+        .arg("--gh-command")
+        .arg(&fake_gh);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "gh sizer repo org-a/org-a-api --output-format text > output/org-a-api.txt",
+        ))
+        .stdout(predicate::str::contains(
+            "gh sizer repo org-b/org-b-api --output-format text > output/org-b-api.txt",
+        ))
+        .stderr(predicate::str::contains(
+            "Found 2 organizations in the acme enterprise\nGenerating script for 2 repositories owned by organizations in the acme enterprise\n",
+        ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "linux_integration_tests")]
+fn repo_command_estimates_repo_size_without_cloning() -> Result<(), Box<dyn std::error::Error>> {
+    let directory = tempfile::tempdir()?;
+    let fake_gh = directory.path().join("gh");
+
+    // A fake `gh` which is authenticated and returns a canned disk usage, in kilobytes
+    fs::write(
+        &fake_gh,
+        r#"#!/bin/sh
+case "$1" in
+  repo)
+    echo '{"diskUsage":3145728}'
+    ;;
+esac
+"#,
+    )?;
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755))?;
+
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("repo")
+        .arg("github/gh-sizer")
+        .arg("--estimate")
+        .arg("--gh-command")
+        .arg(&fake_gh);
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        "github/gh-sizer is approximately 3.00 GiB (large)",
+    ));
+
+    Ok(())
+}
@@ -1,14 +1,40 @@
 use clap::{Parser, Subcommand};
-use exitcode;
-use std::io::{Error, ErrorKind};
+use std::io::ErrorKind;
 use std::path::Path;
 use std::process::Command;
-use tempfile::tempdir;
+use std::time::SystemTime;
 
+use gh_sizer::additional_refs;
+use gh_sizer::aggregate;
+use gh_sizer::clone_cache::{CacheEntry, CloneCache};
+use gh_sizer::enterprise::EnterpriseOrganizationListerImpl;
+use gh_sizer::enums::AggregateFormat;
+use gh_sizer::enums::Interval;
 use gh_sizer::enums::OutputFormat;
 use gh_sizer::enums::ScriptType;
+use gh_sizer::enums::SortBy;
+use gh_sizer::enums::Visibility;
+use gh_sizer::estimate::{self, RepositoryEstimator, RepositoryEstimatorImpl};
 use gh_sizer::generate_script;
+use gh_sizer::gists::{self, GistListerImpl, GistSizerImpl};
 use gh_sizer::github_repository_lister::GitHubRepositoryListerImpl;
+use gh_sizer::github_repository_lister::Repository;
+use gh_sizer::migration_readiness::Profile;
+use gh_sizer::name_pattern::NamePattern;
+use gh_sizer::org;
+use gh_sizer::policy::{self, Policy};
+use gh_sizer::ref_selection::RefSelection;
+use gh_sizer::report::{self, Report};
+use gh_sizer::repository_filter::{self, RepositoryFilter};
+use gh_sizer::repository_sizer::{
+    is_git_repository, parse_sizer_argument, size_repository_at, AnalysisOptions, RepositorySizer,
+    RepositorySizerImpl,
+};
+use gh_sizer::repository_source::{read_repos_file, RepositorySource};
+use gh_sizer::scan;
+use gh_sizer::sizer_report::format_bytes;
+use gh_sizer::sizer_report::Metric;
+use gh_sizer::wiki;
 
 #[cfg(test)]
 mod windows_integration_tests;
@@ -30,11 +56,135 @@ enum Commands {
     /// Run `git-sizer` on a specific repo and output the results to stdout
     Repo {
         #[clap(
+            required_unless_present = "repos_file",
             help = "The owner and repository name of the repo to size, separated by a slash, e.g. `timrogers/gh-sizer`"
         )]
-        repository: String,
+        repository: Option<String>,
+        #[clap(
+            long,
+            conflicts_with = "repository",
+            help = "The path to a file listing the repos to size, one per line, either as `owner/repository` or as a URL. Use `-` to read the list from stdin."
+        )]
+        repos_file: Option<String>,
         #[clap(value_enum, long, short, default_value_t = OutputFormat::Text, help = "The format to use for the output")]
         output_format: OutputFormat,
+        #[clap(
+            long,
+            short,
+            help = "The path to a TOML or YAML policy file declaring limits for the repo. If any limit is exceeded, the violations are included in the output and gh-sizer exits with exit code 3."
+        )]
+        policy: Option<String>,
+        #[clap(
+            long,
+            help = "The name of a migration target profile, e.g. `gei` or `github-com-push-limits`, to assess whether the repo can be migrated to that target"
+        )]
+        profile: Option<String>,
+        #[clap(
+            long,
+            requires = "profile",
+            help = "The path to a TOML file overriding the built-in migration target profiles or declaring additional profiles"
+        )]
+        profiles_file: Option<String>,
+        #[clap(
+            long,
+            help = "The hostname of the GitHub instance the repo is on, e.g. `github.acme.com` for GitHub Enterprise Server or `acme.ghe.com` for GitHub Enterprise Cloud with data residency. Defaults to github.com."
+        )]
+        hostname: Option<String>,
+        #[clap(
+            long,
+            help = "A directory to keep bare clones in between runs. Repos which are already in the cache are refreshed with an incremental fetch rather than cloned again."
+        )]
+        cache_dir: Option<String>,
+        #[clap(
+            long,
+            conflicts_with_all = ["policy", "profile", "cache_dir"],
+            help = "Report an approximate size tier based on the disk usage reported by the GitHub API, without cloning the repo or running git-sizer"
+        )]
+        estimate: bool,
+        #[clap(
+            long,
+            value_name = "N",
+            conflicts_with = "estimate",
+            help = "Report the N largest blobs in the repo's history, with the paths they were stored at and the commit which introduced them"
+        )]
+        top_blobs: Option<usize>,
+        #[clap(
+            long,
+            conflicts_with = "estimate",
+            help = "Recommend Git LFS tracking patterns and paths to remove from history to make the repo smaller, based on the large files in its history, with the estimated size saved by each"
+        )]
+        recommend: bool,
+        #[clap(
+            long,
+            value_name = "N",
+            conflicts_with = "estimate",
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Break down the size of the repo's whole history by path, showing how much each directory has contributed, down to N levels of directories"
+        )]
+        breakdown_depth: Option<u16>,
+        #[clap(
+            value_enum,
+            long,
+            conflicts_with = "estimate",
+            help = "Report how the repo has grown over time, showing the commits, blobs and bytes introduced in each month or year"
+        )]
+        growth: Option<Interval>,
+        #[clap(
+            long = "refs",
+            value_name = "PREFIX",
+            conflicts_with = "estimate",
+            help = "Only size refs whose names start with this prefix, e.g. `refs/heads/main` or `refs/tags`. Can be specified more than once to size refs matching any of the prefixes."
+        )]
+        refs: Vec<String>,
+        #[clap(
+            long,
+            value_name = "PREFIX",
+            conflicts_with = "estimate",
+            help = "Skip refs whose names start with this prefix, e.g. `refs/heads/archive`. Can be specified more than once."
+        )]
+        exclude_refs: Vec<String>,
+        #[clap(
+            long,
+            conflicts_with_all = ["estimate", "cache_dir"],
+            value_parser = repository_filter::parse_date,
+            help = "Only size history from on or after this date, in YYYY-MM-DD format, to see what the repo would weigh if only recent history were migrated. The repo is cloned with `--shallow-since`, so this can't be combined with --cache-dir."
+        )]
+        since: Option<String>,
+        #[clap(
+            long,
+            value_name = "ARGUMENT",
+            allow_hyphen_values = true,
+            conflicts_with = "estimate",
+            value_parser = parse_sizer_argument,
+            help = "An extra argument to pass to git-sizer, e.g. `--sizer-arg=--names=full`. Can be specified more than once. Options which change git-sizer's output format, like `--json`, or which metrics it shows, like `--threshold`, aren't allowed."
+        )]
+        sizer_arg: Vec<String>,
+        #[clap(
+            long,
+            conflicts_with_all = ["estimate", "cache_dir"],
+            help = "Also fetch pull request refs (`refs/pull/*`) into the clone before sizing it, and report how much they add to the repo's size compared with its branches and tags. Migrations and mirrors often include these refs. Can't be combined with --cache-dir."
+        )]
+        include_pull_refs: bool,
+        #[clap(
+            long,
+            value_name = "REFSPEC",
+            conflicts_with_all = ["estimate", "cache_dir"],
+            value_parser = additional_refs::parse_refspec,
+            help = "Also fetch the refs matched by this refspec into the clone before sizing it, and report how much they add to the repo's size compared with its branches and tags, e.g. `+refs/changes/*:refs/changes/*`. The refs must be fetched outside of refs/heads and refs/tags. Can be specified more than once. Can't be combined with --cache-dir."
+        )]
+        fetch_refspec: Vec<String>,
+        #[clap(
+            long,
+            conflicts_with = "estimate",
+            help = "Also report the number and total size of the Git LFS objects referenced by pointer files in the repo's history, which git-sizer doesn't count. Sizes are read from the pointer files, so the objects aren't downloaded."
+        )]
+        include_lfs: bool,
+        #[clap(
+            long,
+            conflicts_with = "estimate",
+            help = "Also clone and size the repo's wiki, which is a separate Git repo, if it has one. The wiki is checked against --policy and --profile too, but extra refs from --include-pull-refs and --fetch-refspec aren't fetched for it. You can also size just a wiki by passing e.g. `github/gh-sizer.wiki` as the repo."
+        )]
+        include_wiki: bool,
         // Hidden options are used for testing and may change between versions without notice.
         #[clap(long, hide = true, default_value = "gh")]
         gh_command: String,
@@ -42,9 +192,22 @@ enum Commands {
     /// Generate a Bash script to run `git-sizer` on all the repos owned by a user or organization and output the results to stdout or files
     GenerateScript {
         #[clap(
-            help = "The owner of the repositories you want to size - either a user or an organization"
+            required_unless_present_any = ["repos_file", "enterprise"],
+            help = "The owners of the repositories you want to size - either users or organizations. You can specify more than one owner to generate a single script covering all of them."
+        )]
+        owners: Vec<String>,
+        #[clap(
+            long,
+            conflicts_with_all = ["owners", "enterprise"],
+            help = "The path to a file listing the repos to size, one per line, either as `owner/repository` or as a URL, instead of sizing all of an owner's repos. Use `-` to read the list from stdin."
+        )]
+        repos_file: Option<String>,
+        #[clap(
+            long,
+            conflicts_with = "owners",
+            help = "The slug of a GitHub Enterprise account. All of the repos owned by the enterprise's organizations will be sized."
         )]
-        owner: String,
+        enterprise: Option<String>,
         #[clap(value_enum, long, short = 's', default_value_t = ScriptType::Bash, help = "The type of script to generate")]
         script_type: ScriptType,
         #[clap(value_enum, long, short = 'f', default_value_t = OutputFormat::Text, help = "The format to use for the output")]
@@ -53,7 +216,7 @@ enum Commands {
             long,
             short = 'd',
             default_value = "output",
-            help = "The directory to save the output files to"
+            help = "The directory to save the output files to. Use `${owner}` to save each owner's output files in a separate directory, e.g. `output/${owner}`."
         )]
         output_directory: String,
         #[clap(
@@ -63,12 +226,369 @@ enum Commands {
             help = "The filename to use for the output files. Use `${owner}` and `${repository}` to include the owner and repository name in the filename. This must be a filename, and cannot include a directory."
         )]
         output_filename: String,
+        #[clap(
+            long,
+            help = "The hostname of the GitHub instance the repos are on, e.g. `github.acme.com` for GitHub Enterprise Server or `acme.ghe.com` for GitHub Enterprise Cloud with data residency. Defaults to github.com."
+        )]
+        hostname: Option<String>,
+        #[clap(
+            long,
+            help = "A directory to keep bare clones in between runs of the generated script. Repos which are already in the cache are refreshed with an incremental fetch rather than cloned again."
+        )]
+        cache_dir: Option<String>,
+        #[clap(
+            long,
+            conflicts_with_all = ["cache_dir", "refs", "exclude_refs", "since", "sizer_arg"],
+            help = "Instead of generating a script, report an approximate size tier for each repo straight away, based on the disk usage reported by the GitHub API when listing the repos, without cloning them or running git-sizer"
+        )]
+        estimate: bool,
+        #[clap(
+            long = "refs",
+            value_name = "PREFIX",
+            help = "When sizing each repo, only size refs whose names start with this prefix, e.g. `refs/heads/main` or `refs/tags`. Can be specified more than once to size refs matching any of the prefixes."
+        )]
+        refs: Vec<String>,
+        #[clap(
+            long,
+            value_name = "PREFIX",
+            help = "When sizing each repo, skip refs whose names start with this prefix, e.g. `refs/heads/archive`. Can be specified more than once."
+        )]
+        exclude_refs: Vec<String>,
+        #[clap(
+            long,
+            conflicts_with = "cache_dir",
+            value_parser = repository_filter::parse_date,
+            help = "When sizing each repo, only size history from on or after this date, in YYYY-MM-DD format, to see what the repo would weigh if only recent history were migrated. Repos are cloned with `--shallow-since`, so this can't be combined with --cache-dir."
+        )]
+        since: Option<String>,
+        #[clap(
+            long,
+            value_name = "ARGUMENT",
+            allow_hyphen_values = true,
+            value_parser = parse_sizer_argument,
+            help = "An extra argument to pass to git-sizer when sizing each repo, e.g. `--sizer-arg=--names=full`. Can be specified more than once. Options which change git-sizer's output format, like `--json`, or which metrics it shows, like `--threshold`, aren't allowed."
+        )]
+        sizer_arg: Vec<String>,
+        #[clap(
+            long,
+            conflicts_with = "estimate",
+            help = "Also size each repo's wiki, if it has one, writing the results to a separate file named by adding `.wiki` to the repo's name in --output-filename, e.g. `${repository}.wiki.txt`. Repos without wikis are noted when the script runs, and no file is written for them."
+        )]
+        include_wiki: bool,
+        #[clap(
+            long,
+            conflicts_with = "repos_file",
+            help = "Skip repos which are forks of another repo"
+        )]
+        exclude_forks: bool,
+        #[clap(
+            long,
+            conflicts_with = "repos_file",
+            help = "Skip repos which have been archived"
+        )]
+        exclude_archived: bool,
+        #[clap(
+            value_enum,
+            long,
+            conflicts_with = "repos_file",
+            help = "Only include repos with this visibility"
+        )]
+        visibility: Option<Visibility>,
+        #[clap(
+            long,
+            conflicts_with = "repos_file",
+            help = "Only include repos whose primary language is this language, e.g. `Rust`"
+        )]
+        language: Option<String>,
+        #[clap(
+            long,
+            conflicts_with = "repos_file",
+            help = "Only include repos with this topic. If specified more than once, repos must have all of the topics."
+        )]
+        topic: Vec<String>,
+        #[clap(
+            long,
+            conflicts_with = "repos_file",
+            value_parser = repository_filter::parse_date,
+            help = "Only include repos which have been pushed to on or after this date, in YYYY-MM-DD format"
+        )]
+        pushed_since: Option<String>,
+        #[clap(
+            long,
+            conflicts_with = "repos_file",
+            value_parser = policy::parse_size,
+            help = "Only include repos whose disk usage reported by the GitHub API is at least this size, e.g. `100 MiB`"
+        )]
+        min_size: Option<u64>,
+        #[clap(
+            long,
+            conflicts_with = "repos_file",
+            value_parser = policy::parse_size,
+            help = "Only include repos whose disk usage reported by the GitHub API is at most this size, e.g. `10 GiB`"
+        )]
+        max_size: Option<u64>,
+        #[clap(
+            value_enum,
+            long,
+            help = "The order to size repos in: `size` for the largest first, according to the disk usage reported by the GitHub API, `name` for alphabetical order, or `pushed` for the most recently pushed first. Defaults to the order the repos are listed in."
+        )]
+        sort_by: Option<SortBy>,
+        #[clap(
+            long,
+            requires = "sort_by",
+            help = "Reverse the order chosen with --sort-by, e.g. to size the smallest repos first"
+        )]
+        reverse: bool,
+        #[clap(
+            long,
+            value_parser = NamePattern::parse,
+            help = "Only include repos whose names match this pattern - either a glob, e.g. `api-*`, or a regular expression between slashes, e.g. `/^api-v[0-9]+$/`. Can be specified more than once to include repos matching any of the patterns."
+        )]
+        include: Vec<NamePattern>,
+        #[clap(
+            long,
+            value_parser = NamePattern::parse,
+            help = "Skip repos whose names match this pattern - either a glob, e.g. `*-archive`, or a regular expression between slashes. Can be specified more than once."
+        )]
+        exclude: Vec<NamePattern>,
         // Hidden options are used for testing and may change between versions without notice.
         #[clap(long, short = 'c', default_value = "gh sizer", hide = true)]
         gh_sizer_command: String,
         #[clap(long, hide = true, default_value = "gh", hide = true)]
         gh_command: String,
     },
+    /// Run `git-sizer` on all the repos owned by a user or organization, sizing several repos at once, and save the results to files
+    Org {
+        #[clap(
+            required_unless_present = "enterprise",
+            help = "The owner of the repositories you want to size - either a user or an organization"
+        )]
+        owner: Option<String>,
+        #[clap(
+            long,
+            conflicts_with = "owner",
+            help = "The slug of a GitHub Enterprise account. All of the repos owned by the enterprise's organizations will be sized."
+        )]
+        enterprise: Option<String>,
+        #[clap(
+            long,
+            short = 'j',
+            default_value_t = 4,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "The number of repos to clone and size at the same time"
+        )]
+        jobs: u16,
+        #[clap(value_enum, long, short = 'f', default_value_t = OutputFormat::Text, help = "The format to use for the output")]
+        output_format: OutputFormat,
+        #[clap(
+            long,
+            short = 'd',
+            default_value = "output",
+            help = "The directory to save the output files to"
+        )]
+        output_directory: String,
+        #[clap(
+            long,
+            short = 'n',
+            default_value = "${repository}.txt",
+            help = "The filename to use for the output files. Use `${owner}` and `${repository}` to include the owner and repository name in the filename. This must be a filename, and cannot include a directory."
+        )]
+        output_filename: String,
+        #[clap(
+            long,
+            help = "The name of a migration target profile, e.g. `gei` or `github-com-push-limits`, to assess whether each repo can be migrated to that target"
+        )]
+        profile: Option<String>,
+        #[clap(
+            long,
+            requires = "profile",
+            help = "The path to a TOML file overriding the built-in migration target profiles or declaring additional profiles"
+        )]
+        profiles_file: Option<String>,
+        #[clap(
+            long,
+            help = "The hostname of the GitHub instance the repos are on, e.g. `github.acme.com` for GitHub Enterprise Server or `acme.ghe.com` for GitHub Enterprise Cloud with data residency. Defaults to github.com."
+        )]
+        hostname: Option<String>,
+        #[clap(
+            long,
+            help = "A directory to keep bare clones in between runs. Repos which are already in the cache are refreshed with an incremental fetch rather than cloned again."
+        )]
+        cache_dir: Option<String>,
+        // Hidden options are used for testing and may change between versions without notice.
+        #[clap(long, hide = true, default_value = "gh")]
+        gh_command: String,
+    },
+    /// Run `git-sizer` on all of a user's gists, sizing several gists at once, and save the results to files
+    Gists {
+        #[clap(help = "The user whose gists you want to size")]
+        user: String,
+        #[clap(
+            long,
+            short = 'j',
+            default_value_t = 4,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "The number of gists to clone and size at the same time"
+        )]
+        jobs: u16,
+        #[clap(value_enum, long, short = 'f', default_value_t = OutputFormat::Text, help = "The format to use for the output")]
+        output_format: OutputFormat,
+        #[clap(
+            long,
+            short = 'd',
+            default_value = "output",
+            help = "The directory to save the output files to"
+        )]
+        output_directory: String,
+        #[clap(
+            long,
+            short = 'n',
+            default_value = "${repository}.txt",
+            help = "The filename to use for the output files. Use `${owner}` and `${repository}` to include the user and the gist's ID in the filename. This must be a filename, and cannot include a directory."
+        )]
+        output_filename: String,
+        #[clap(
+            long,
+            short,
+            help = "The path to a TOML or YAML policy file declaring limits for the gists. If any limit is exceeded, the violations are included in the output and gh-sizer exits with exit code 3."
+        )]
+        policy: Option<String>,
+        #[clap(
+            long,
+            help = "The name of a migration target profile, e.g. `gei` or `github-com-push-limits`, to assess whether each gist can be migrated to that target"
+        )]
+        profile: Option<String>,
+        #[clap(
+            long,
+            requires = "profile",
+            help = "The path to a TOML file overriding the built-in migration target profiles or declaring additional profiles"
+        )]
+        profiles_file: Option<String>,
+        #[clap(
+            long,
+            help = "The hostname of the GitHub instance the gists are on, e.g. `github.acme.com` for GitHub Enterprise Server. Defaults to github.com."
+        )]
+        hostname: Option<String>,
+        // Hidden options are used for testing and may change between versions without notice.
+        #[clap(long, hide = true, default_value = "gh")]
+        gh_command: String,
+    },
+    /// Run `git-sizer` on a repo which has already been cloned, either bare or with a working copy, and output the results to stdout
+    Local {
+        #[clap(help = "The path to the local repository to size")]
+        path: String,
+        #[clap(value_enum, long, short, default_value_t = OutputFormat::Text, help = "The format to use for the output")]
+        output_format: OutputFormat,
+        #[clap(
+            long,
+            short,
+            help = "The path to a TOML or YAML policy file declaring limits for the repo. If any limit is exceeded, the violations are included in the output and gh-sizer exits with exit code 3."
+        )]
+        policy: Option<String>,
+        #[clap(
+            long,
+            help = "The name of a migration target profile, e.g. `gei` or `github-com-push-limits`, to assess whether the repo can be migrated to that target"
+        )]
+        profile: Option<String>,
+        #[clap(
+            long,
+            requires = "profile",
+            help = "The path to a TOML file overriding the built-in migration target profiles or declaring additional profiles"
+        )]
+        profiles_file: Option<String>,
+    },
+    /// Find all of the repos, bare or with a working copy, in a directory tree, size them and save the results to files
+    Scan {
+        #[clap(help = "The directory to search for repositories")]
+        directory: String,
+        #[clap(
+            long,
+            short = 'j',
+            default_value_t = 4,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "The number of repos to size at the same time"
+        )]
+        jobs: u16,
+        #[clap(value_enum, long, short = 'f', default_value_t = OutputFormat::Text, help = "The format to use for the output")]
+        output_format: OutputFormat,
+        #[clap(
+            long,
+            short = 'd',
+            default_value = "output",
+            help = "The directory to save the output files to"
+        )]
+        output_directory: String,
+        #[clap(
+            long,
+            short = 'n',
+            default_value = "${repository}.txt",
+            help = "The filename to use for the output files. Use `${owner}` and `${repository}` to include the name of the directory containing the repo and the repo's own directory name, without any `.git` suffix, in the filename. This must be a filename, and cannot include a directory."
+        )]
+        output_filename: String,
+        #[clap(
+            long,
+            short,
+            help = "The path to a TOML or YAML policy file declaring limits for the repos. If any limit is exceeded, the violations are included in the output and gh-sizer exits with exit code 3."
+        )]
+        policy: Option<String>,
+        #[clap(
+            long,
+            help = "The name of a migration target profile, e.g. `gei` or `github-com-push-limits`, to assess whether each repo can be migrated to that target"
+        )]
+        profile: Option<String>,
+        #[clap(
+            long,
+            requires = "profile",
+            help = "The path to a TOML file overriding the built-in migration target profiles or declaring additional profiles"
+        )]
+        profiles_file: Option<String>,
+    },
+    /// Manage the clone cache used by the `--cache-dir` option
+    Cache {
+        #[clap(subcommand)]
+        command: CacheCommands,
+    },
+    /// Combine the result files in a directory, e.g. from running a generated script, into a single report
+    Aggregate {
+        #[clap(help = "The directory containing the result files")]
+        directory: String,
+        #[clap(
+            long,
+            short = 'n',
+            default_value = "${repository}.txt",
+            help = "The filename template used when the result files were generated. Files in the directory which don't match the template are ignored."
+        )]
+        output_filename: String,
+        #[clap(value_enum, long, short = 'f', default_value_t = AggregateFormat::Csv, help = "The format to use for the output")]
+        output_format: AggregateFormat,
+        #[clap(value_enum, long, short = 's', default_value_t = Metric::UniqueBlobSize, help = "The metric to sort repos by, largest first")]
+        sort_by: Metric,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheCommands {
+    /// List the repos in the clone cache with their sizes, most recently used first
+    List {
+        #[clap(long, help = "The clone cache directory")]
+        cache_dir: String,
+    },
+    /// Remove the least recently used repos from the clone cache until it fits within a size budget
+    Prune {
+        #[clap(long, help = "The clone cache directory")]
+        cache_dir: String,
+        #[clap(
+            long,
+            value_parser = policy::parse_size,
+            help = "The maximum total size of the clone cache, e.g. `100 GiB`"
+        )]
+        max_size: u64,
+    },
+    /// Remove every repo from the clone cache
+    Clear {
+        #[clap(long, help = "The clone cache directory")]
+        cache_dir: String,
+    },
 }
 
 fn command_exists(command: &str) -> bool {
@@ -76,7 +596,7 @@ fn command_exists(command: &str) -> bool {
         Ok(_) => true,
         Err(e) => {
             if let ErrorKind::NotFound = e.kind() {
-                return false;
+                false
             } else {
                 panic!("Command {} returned an unexpected error: {}", command, e);
             }
@@ -89,53 +609,202 @@ fn command_succeeds(command: &str, args: Vec<String>) -> bool {
         Ok(_) => true,
         Err(e) => {
             eprintln!("Command {} returned an unexpected error: {}", command, e);
-            return false;
+            false
         }
     }
 }
 
-fn run_git_sizer_on_repository(nwo: &str, format: OutputFormat) -> Result<String, Error> {
-    let temporary_directory = tempdir().expect("Failed to create temporary directory");
-    let temporary_directory_path = temporary_directory.path().to_str().unwrap();
+fn ensure_gh_is_authenticated(gh_command: &str, hostname: &Option<String>) {
+    if !command_exists(gh_command) {
+        eprintln!("`gh` not found. To use gh-sizer, please install the GitHub CLI (https://cli.github.com).");
+        std::process::exit(exitcode::DATAERR);
+    }
 
-    eprintln!("Cloning {} from GitHub...", &nwo);
+    let mut auth_status_args = vec!["auth".to_string(), "status".to_string()];
 
-    let mut binding = Command::new("gh");
-    let clone_command = binding
-        .arg("repo")
-        .arg("clone")
-        .arg(&nwo)
-        .arg(temporary_directory_path)
-        .arg("--")
-        .arg("--bare");
+    if let Some(hostname) = hostname {
+        auth_status_args.push("--hostname".to_string());
+        auth_status_args.push(hostname.to_owned());
+    }
 
-    let clone_output = clone_command.output()?;
+    if !command_succeeds(gh_command, auth_status_args) {
+        eprintln!("You don't seem to be authenticated with the GitHub CLI, or your current access token is invalid. To authenticate, run `gh auth login`.");
+        std::process::exit(exitcode::DATAERR);
+    }
+}
 
-    if !clone_output.status.success() {
-        eprintln!("{}", String::from_utf8_lossy(&clone_output.stderr));
+fn ensure_git_sizer_is_installed() {
+    if !command_exists("git-sizer") {
+        eprintln!("`git-sizer` not found. To use gh-sizer, please install git-sizer (https://github.com/github/git-sizer).");
         std::process::exit(exitcode::DATAERR);
     }
+}
 
-    eprintln!("Running git-sizer on cloned repository...");
+fn ensure_output_filename_is_not_a_path(output_filename: &str) {
+    if Path::new(output_filename).components().count() > 1 {
+        eprintln!("--output-filename must be a filename, not a path");
+        std::process::exit(exitcode::DATAERR);
+    }
+}
 
-    let mut sizer_command = Command::new("git-sizer");
+fn load_profile(profile: &Option<String>, profiles_file: &Option<String>) -> Option<Profile> {
+    profile
+        .as_ref()
+        .map(|name| match Profile::find(name, profiles_file.as_deref()) {
+            Ok(profile) => profile,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(exitcode::CONFIG);
+            }
+        })
+}
 
-    sizer_command.current_dir(temporary_directory_path);
-    sizer_command.arg("--verbose");
+fn load_policy(policy: &Option<String>) -> Option<Policy> {
+    policy.as_ref().map(|path| match Policy::from_file(path) {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(exitcode::CONFIG);
+        }
+    })
+}
+
+fn print_report_and_exit(
+    result: Result<Report, std::io::Error>,
+    output_format: OutputFormat,
+    policy: Option<&Policy>,
+    profile: Option<&Profile>,
+) -> ! {
+    match result {
+        Ok(report) => {
+            let report = report.check(policy, profile);
+
+            println!("{}", report.render(output_format));
 
-    if matches!(format, OutputFormat::Json) {
-        sizer_command.arg("--json");
+            if report.violation_count() > 0 {
+                std::process::exit(policy::POLICY_VIOLATED)
+            } else {
+                std::process::exit(exitcode::OK)
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(exitcode::DATAERR);
+        }
     }
+}
+
+fn describe_cache_entry(entry: &CacheEntry) -> String {
+    let hours = SystemTime::now()
+        .duration_since(entry.last_used)
+        .unwrap_or_default()
+        .as_secs()
+        / 3600;
 
-    let sizer_command_output = sizer_command.output()?;
+    let last_used = match hours {
+        0 => "less than an hour ago".to_string(),
+        1..=47 => format!("{} hours ago", hours),
+        _ => format!("{} days ago", hours / 24),
+    };
+
+    format!(
+        "{} ({}, last used {})",
+        entry.name,
+        format_bytes(entry.size),
+        last_used
+    )
+}
 
-    if !sizer_command_output.status.success() {
-        eprintln!("{}", String::from_utf8_lossy(&sizer_command_output.stderr));
+fn enterprise_repository_source(
+    enterprise: &str,
+    hostname: &Option<String>,
+    gh_command: &str,
+) -> RepositorySource {
+    let enterprise_organization_lister = EnterpriseOrganizationListerImpl {
+        hostname: hostname.to_owned(),
+        gh_command: gh_command.to_string(),
+    };
+
+    match RepositorySource::for_enterprise(
+        enterprise,
+        &enterprise_organization_lister,
+        &mut std::io::stderr(),
+    ) {
+        Ok(repository_source) => repository_source,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(exitcode::DATAERR);
+        }
+    }
+}
+
+fn print_estimates_and_exit(
+    repositories: &[Repository],
+    repository_estimator: &impl RepositoryEstimator,
+    output_format: OutputFormat,
+) -> ! {
+    let (estimates, failed_count) =
+        match estimate::estimate_all(repositories, repository_estimator, &mut std::io::stderr()) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(exitcode::DATAERR);
+            }
+        };
+
+    println!("{}", estimate::render_all(&estimates, output_format));
+
+    if failed_count > 0 {
         std::process::exit(exitcode::DATAERR);
+    } else {
+        std::process::exit(exitcode::OK);
     }
+}
+
+fn print_reports_and_exit(
+    repositories: &[Repository],
+    repository_sizer: &impl RepositorySizer,
+    output_format: OutputFormat,
+    policy: Option<&Policy>,
+    profile: Option<&Profile>,
+) -> ! {
+    let mut reports = Vec::new();
+    let mut failed_count = 0;
 
-    let output_text = String::from_utf8_lossy(&sizer_command_output.stdout);
-    Ok(output_text.to_string())
+    for (index, repository) in repositories.iter().enumerate() {
+        eprintln!(
+            "Sizing repo {} ({}/{})",
+            repository.nwo(),
+            index + 1,
+            repositories.len()
+        );
+
+        match repository_sizer.call(&repository.nwo()) {
+            Ok(report) => reports.push((repository.nwo(), report.check(policy, profile))),
+            Err(e) => {
+                eprintln!(
+                    "Failed to size repo {}: {}",
+                    repository.nwo(),
+                    e.to_string().trim_end()
+                );
+                failed_count += 1;
+            }
+        }
+    }
+
+    println!("{}", report::render_all(&reports, output_format));
+
+    let violated_policy = reports
+        .iter()
+        .any(|(_, report)| report.violation_count() > 0);
+
+    if failed_count > 0 {
+        std::process::exit(exitcode::DATAERR);
+    } else if violated_policy {
+        std::process::exit(policy::POLICY_VIOLATED);
+    } else {
+        std::process::exit(exitcode::OK);
+    }
 }
 
 fn main() {
@@ -144,25 +813,267 @@ fn main() {
     match &args.command {
         Commands::Repo {
             repository,
+            repos_file,
             output_format,
+            policy,
+            profile,
+            profiles_file,
+            hostname,
+            cache_dir,
+            estimate,
+            top_blobs,
+            recommend,
+            breakdown_depth,
+            growth,
+            refs,
+            exclude_refs,
+            since,
+            sizer_arg,
+            include_pull_refs,
+            fetch_refspec,
+            include_lfs,
+            include_wiki,
             gh_command,
         } => {
-            if !command_exists(gh_command) {
-                eprintln!("`gh` not found. To use gh-sizer, please install the GitHub CLI (https://cli.github.com).");
-                std::process::exit(exitcode::DATAERR);
+            let policy = load_policy(policy);
+            let profile = load_profile(profile, profiles_file);
+
+            ensure_gh_is_authenticated(gh_command, hostname);
+
+            if *estimate {
+                let repository_estimator = RepositoryEstimatorImpl {
+                    hostname: hostname.to_owned(),
+                    gh_command: gh_command.to_owned(),
+                };
+
+                match (repository, repos_file) {
+                    (Some(repository), _) => match repository_estimator.call(repository) {
+                        Ok(estimate) => {
+                            println!("{}", estimate.render(output_format.to_owned()));
+                            std::process::exit(exitcode::OK);
+                        }
+                        Err(e) => {
+                            eprintln!("{}", e.to_string().trim_end());
+                            std::process::exit(exitcode::DATAERR);
+                        }
+                    },
+                    (None, Some(repos_file)) => {
+                        match read_repos_file(repos_file, hostname.as_deref()) {
+                            Ok(repositories) => print_estimates_and_exit(
+                                &repositories,
+                                &repository_estimator,
+                                output_format.to_owned(),
+                            ),
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                std::process::exit(exitcode::DATAERR);
+                            }
+                        }
+                    }
+                    (None, None) => unreachable!("clap requires a repository or a repos file"),
+                }
             }
 
-            if !command_succeeds(gh_command, vec!["auth".to_string(), "status".to_string()]) {
-                eprintln!("You don't seem to be authenticated with the GitHub CLI, or your current access token is invalid. To authenticate, run `gh auth login`.");
-                std::process::exit(exitcode::DATAERR);
+            ensure_git_sizer_is_installed();
+
+            let repository_sizer = RepositorySizerImpl {
+                hostname: hostname.to_owned(),
+                cache: cache_dir.as_ref().map(CloneCache::new),
+                ref_selection: RefSelection {
+                    refs: refs.to_owned(),
+                    exclude_refs: exclude_refs.to_owned(),
+                    since: since.to_owned(),
+                },
+                sizer_arguments: sizer_arg.to_owned(),
+                analysis_options: AnalysisOptions {
+                    top_blobs: *top_blobs,
+                    recommend: *recommend,
+                    breakdown_depth: breakdown_depth.map(usize::from),
+                    growth: *growth,
+                    include_lfs: *include_lfs,
+                },
+                fetch_refspecs: include_pull_refs
+                    .then(|| additional_refs::PULL_REQUEST_REFSPEC.to_string())
+                    .into_iter()
+                    .chain(fetch_refspec.iter().cloned())
+                    .collect(),
+                include_wiki: *include_wiki,
+            };
+
+            match (repository, repos_file) {
+                (Some(repository), _) => {
+                    let result = repository_sizer.call(repository);
+
+                    // A wiki which has never had a page created can't be cloned, which is
+                    // expected, so note it rather than failing, e.g. in generated scripts
+                    if let Err(e) = &result {
+                        if wiki::is_wiki(repository) && wiki::is_missing_repository(e) {
+                            eprintln!(
+                                "{} doesn't have a wiki",
+                                repository.trim_end_matches(".wiki")
+                            );
+                            std::process::exit(exitcode::OK);
+                        }
+                    }
+
+                    print_report_and_exit(
+                        result,
+                        output_format.to_owned(),
+                        policy.as_ref(),
+                        profile.as_ref(),
+                    )
+                }
+                (None, Some(repos_file)) => {
+                    match read_repos_file(repos_file, hostname.as_deref()) {
+                        Ok(repositories) => print_reports_and_exit(
+                            &repositories,
+                            &repository_sizer,
+                            output_format.to_owned(),
+                            policy.as_ref(),
+                            profile.as_ref(),
+                        ),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            std::process::exit(exitcode::DATAERR);
+                        }
+                    }
+                }
+                (None, None) => unreachable!("clap requires a repository or a repos file"),
             }
+        }
+        Commands::GenerateScript {
+            owners,
+            repos_file,
+            enterprise,
+            script_type,
+            output_format,
+            output_directory,
+            output_filename,
+            hostname,
+            cache_dir,
+            estimate,
+            refs,
+            exclude_refs,
+            since,
+            sizer_arg,
+            include_wiki,
+            exclude_forks,
+            exclude_archived,
+            visibility,
+            language,
+            topic,
+            pushed_since,
+            min_size,
+            max_size,
+            sort_by,
+            reverse,
+            include,
+            exclude,
+            gh_sizer_command,
+            gh_command,
+        } => {
+            ensure_gh_is_authenticated(gh_command, hostname);
 
-            if !command_exists("git-sizer") {
-                eprintln!("`git-sizer` not found. To use gh-sizer, please install git-sizer (https://github.com/github/git-sizer).");
-                std::process::exit(exitcode::DATAERR);
+            ensure_output_filename_is_not_a_path(output_filename);
+
+            let mut repo_arguments = Vec::new();
+
+            if let Some(hostname) = hostname {
+                repo_arguments.push("--hostname".to_string());
+                repo_arguments.push(hostname.to_owned());
             }
 
-            match run_git_sizer_on_repository(repository, output_format.to_owned()) {
+            if let Some(cache_dir) = cache_dir {
+                repo_arguments.push("--cache-dir".to_string());
+                repo_arguments.push(cache_dir.to_owned());
+            }
+
+            repo_arguments.extend(
+                RefSelection {
+                    refs: refs.to_owned(),
+                    exclude_refs: exclude_refs.to_owned(),
+                    since: since.to_owned(),
+                }
+                .repo_arguments(),
+            );
+
+            for argument in sizer_arg {
+                // Use the `--sizer-arg=VALUE` form, so the value can't be mistaken for an option
+                repo_arguments.push(format!("--sizer-arg={}", argument));
+            }
+
+            let repository_source = match (repos_file, enterprise) {
+                (Some(repos_file), _) => RepositorySource::ReposFile {
+                    path: repos_file.to_owned(),
+                    hostname: hostname.to_owned(),
+                },
+                (None, Some(enterprise)) => {
+                    enterprise_repository_source(enterprise, hostname, gh_command)
+                }
+                (None, None) => RepositorySource::Owners(owners.to_owned()),
+            };
+
+            let repository_filter = RepositoryFilter {
+                exclude_forks: *exclude_forks,
+                exclude_archived: *exclude_archived,
+                visibility: *visibility,
+                language: language.to_owned(),
+                topics: topic.to_owned(),
+                pushed_since: pushed_since.to_owned(),
+                min_size: *min_size,
+                max_size: *max_size,
+                include: include.to_owned(),
+                exclude: exclude.to_owned(),
+            };
+            let github_repository_lister = GitHubRepositoryListerImpl {
+                hostname: hostname.to_owned(),
+                gh_command: gh_command.to_owned(),
+            };
+
+            // The repos' disk usage is fetched when they are listed, so the estimates can be
+            // reported straight away, rather than calling the API again for each repo
+            if *estimate {
+                let mut repositories = match repository_source.list(
+                    &repository_filter,
+                    &github_repository_lister,
+                    &mut std::io::stderr(),
+                ) {
+                    Ok(repositories) => repositories,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(exitcode::DATAERR);
+                    }
+                };
+
+                if let Some(sort_by) = sort_by {
+                    repository_filter::sort(&mut repositories, *sort_by, *reverse);
+                }
+
+                print_estimates_and_exit(
+                    &repositories,
+                    &RepositoryEstimatorImpl {
+                        hostname: hostname.to_owned(),
+                        gh_command: gh_command.to_owned(),
+                    },
+                    output_format.to_owned(),
+                );
+            }
+
+            match generate_script::call(
+                &repository_source,
+                script_type.to_owned(),
+                output_format.to_owned(),
+                output_directory,
+                output_filename,
+                gh_sizer_command,
+                &repo_arguments,
+                &repository_filter,
+                *sort_by,
+                *reverse,
+                *include_wiki,
+                &github_repository_lister,
+                &mut std::io::stderr(),
+            ) {
                 Ok(output) => {
                     println!("{}", output);
                     std::process::exit(exitcode::OK);
@@ -173,42 +1084,244 @@ fn main() {
                 }
             }
         }
-        Commands::GenerateScript {
+        Commands::Org {
             owner,
-            script_type,
+            enterprise,
+            jobs,
             output_format,
             output_directory,
             output_filename,
-            gh_sizer_command,
+            profile,
+            profiles_file,
+            hostname,
+            cache_dir,
             gh_command,
         } => {
-            if !command_exists(gh_command) {
-                eprintln!("`gh` not found. To use gh-sizer, please install the GitHub CLI (https://cli.github.com).");
-                std::process::exit(exitcode::DATAERR);
+            let profile = load_profile(profile, profiles_file);
+
+            ensure_gh_is_authenticated(gh_command, hostname);
+            ensure_git_sizer_is_installed();
+            ensure_output_filename_is_not_a_path(output_filename);
+
+            let repository_source = match (owner, enterprise) {
+                (_, Some(enterprise)) => {
+                    enterprise_repository_source(enterprise, hostname, gh_command)
+                }
+                (Some(owner), None) => RepositorySource::Owners(vec![owner.to_owned()]),
+                (None, None) => unreachable!("clap requires an owner or an enterprise"),
+            };
+
+            match org::call(
+                &repository_source,
+                usize::from(*jobs),
+                output_format.to_owned(),
+                output_directory,
+                output_filename,
+                profile.as_ref(),
+                &GitHubRepositoryListerImpl {
+                    hostname: hostname.to_owned(),
+                    gh_command: gh_command.to_owned(),
+                },
+                &RepositorySizerImpl {
+                    hostname: hostname.to_owned(),
+                    cache: cache_dir.as_ref().map(CloneCache::new),
+                    ..Default::default()
+                },
+                &mut std::io::stderr(),
+            ) {
+                Ok(summary) => {
+                    if summary.failed.is_empty() {
+                        std::process::exit(exitcode::OK);
+                    } else {
+                        std::process::exit(exitcode::DATAERR);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(exitcode::DATAERR);
+                }
             }
+        }
+        Commands::Gists {
+            user,
+            jobs,
+            output_format,
+            output_directory,
+            output_filename,
+            policy,
+            profile,
+            profiles_file,
+            hostname,
+            gh_command,
+        } => {
+            let policy = load_policy(policy);
+            let profile = load_profile(profile, profiles_file);
 
-            if !command_succeeds(gh_command, vec!["auth".to_string(), "status".to_string()]) {
-                eprintln!("You don't seem to be authenticated with the GitHub CLI, or your current access token is invalid. To authenticate, run `gh auth login`.");
-                std::process::exit(exitcode::DATAERR);
+            ensure_gh_is_authenticated(gh_command, hostname);
+            ensure_git_sizer_is_installed();
+            ensure_output_filename_is_not_a_path(output_filename);
+
+            match gists::call(
+                user,
+                usize::from(*jobs),
+                output_format.to_owned(),
+                output_directory,
+                output_filename,
+                policy.as_ref(),
+                profile.as_ref(),
+                &GistListerImpl {
+                    hostname: hostname.to_owned(),
+                    gh_command: gh_command.to_owned(),
+                },
+                &GistSizerImpl {
+                    hostname: hostname.to_owned(),
+                    gh_command: gh_command.to_owned(),
+                },
+                &mut std::io::stderr(),
+            ) {
+                Ok(summary) => {
+                    if !summary.failed.is_empty() {
+                        std::process::exit(exitcode::DATAERR);
+                    } else if !summary.violated_policy.is_empty() {
+                        std::process::exit(policy::POLICY_VIOLATED);
+                    } else {
+                        std::process::exit(exitcode::OK);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(exitcode::DATAERR);
+                }
             }
+        }
+        Commands::Local {
+            path,
+            output_format,
+            policy,
+            profile,
+            profiles_file,
+        } => {
+            let policy = load_policy(policy);
+            let profile = load_profile(profile, profiles_file);
 
-            if Path::new(output_filename).components().count() > 1 {
-                eprintln!("--output-filename must be a filename, not a path");
+            if !is_git_repository(Path::new(path)) {
+                eprintln!("{} is not a Git repository", path);
                 std::process::exit(exitcode::DATAERR);
             }
 
-            match generate_script::call(
-                owner,
-                script_type.to_owned(),
+            ensure_git_sizer_is_installed();
+
+            print_report_and_exit(
+                size_repository_at(Path::new(path), &[]).map(Report::new),
+                output_format.to_owned(),
+                policy.as_ref(),
+                profile.as_ref(),
+            );
+        }
+        Commands::Scan {
+            directory,
+            jobs,
+            output_format,
+            output_directory,
+            output_filename,
+            policy,
+            profile,
+            profiles_file,
+        } => {
+            let policy = load_policy(policy);
+            let profile = load_profile(profile, profiles_file);
+
+            ensure_git_sizer_is_installed();
+            ensure_output_filename_is_not_a_path(output_filename);
+
+            match scan::call(
+                directory,
+                usize::from(*jobs),
                 output_format.to_owned(),
                 output_directory,
                 output_filename,
-                gh_sizer_command,
-                &GitHubRepositoryListerImpl {},
+                policy.as_ref(),
+                profile.as_ref(),
+                &mut std::io::stderr(),
+            ) {
+                Ok(summary) => {
+                    if !summary.failed.is_empty() {
+                        std::process::exit(exitcode::DATAERR);
+                    } else if !summary.violated_policy.is_empty() {
+                        std::process::exit(policy::POLICY_VIOLATED);
+                    } else {
+                        std::process::exit(exitcode::OK);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(exitcode::DATAERR);
+                }
+            }
+        }
+        Commands::Cache { command } => {
+            let result = match command {
+                CacheCommands::List { cache_dir } => {
+                    CloneCache::new(cache_dir).entries().map(|entries| {
+                        for entry in &entries {
+                            println!("{}", describe_cache_entry(entry));
+                        }
+
+                        eprintln!(
+                            "{} repositories in the clone cache, using {}",
+                            entries.len(),
+                            format_bytes(entries.iter().map(|entry| entry.size).sum())
+                        );
+                    })
+                }
+                CacheCommands::Prune {
+                    cache_dir,
+                    max_size,
+                } => CloneCache::new(cache_dir).prune(*max_size).map(|removed| {
+                    for entry in &removed {
+                        println!("Removed {}", describe_cache_entry(entry));
+                    }
+
+                    eprintln!(
+                        "Removed {} repositories from the clone cache, freeing {}",
+                        removed.len(),
+                        format_bytes(removed.iter().map(|entry| entry.size).sum())
+                    );
+                }),
+                CacheCommands::Clear { cache_dir } => {
+                    CloneCache::new(cache_dir).clear().map(|removed| {
+                        eprintln!(
+                            "Removed {} repositories from the clone cache, freeing {}",
+                            removed.len(),
+                            format_bytes(removed.iter().map(|entry| entry.size).sum())
+                        );
+                    })
+                }
+            };
+
+            match result {
+                Ok(()) => std::process::exit(exitcode::OK),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(exitcode::DATAERR);
+                }
+            }
+        }
+        Commands::Aggregate {
+            directory,
+            output_filename,
+            output_format,
+            sort_by,
+        } => {
+            match aggregate::call(
+                directory,
+                output_filename,
+                *sort_by,
+                output_format.to_owned(),
                 &mut std::io::stderr(),
             ) {
                 Ok(output) => {
-                    println!("{}", output);
+                    print!("{}", output);
                     std::process::exit(exitcode::OK);
                 }
                 Err(e) => {
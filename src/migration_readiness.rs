@@ -0,0 +1,329 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind};
+
+use crate::policy::RawSize;
+use crate::sizer_report::{format_bytes, SizerReport};
+
+const MEBIBYTE: u64 = 1024 * 1024;
+const GIBIBYTE: u64 = 1024 * MEBIBYTE;
+
+/// The limits imposed by a migration target, e.g. GitHub Enterprise Importer
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// The largest single file that the target accepts
+    pub max_file_size: Option<u64>,
+    /// The largest total repository size that the target accepts
+    pub max_repository_size: Option<u64>,
+    /// The largest amount of data that the target accepts in a single push
+    pub max_push_size: Option<u64>,
+    /// How close to a limit, as a fraction of it, a repository can get before it is at risk
+    pub at_risk_ratio: f64,
+}
+
+/// Overrides for built-in profiles, or additional profiles, loaded from a TOML file, e.g.
+///
+/// ```toml
+/// [profiles.gei]
+/// at_risk_ratio = 0.5
+///
+/// [profiles.our-ghes]
+/// max_file_size = "100 MiB"
+/// max_repository_size = "10 GiB"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfilesFile {
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileOverrides>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileOverrides {
+    max_file_size: Option<RawSize>,
+    max_repository_size: Option<RawSize>,
+    max_push_size: Option<RawSize>,
+    at_risk_ratio: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Migratable,
+    AtRisk,
+    Blocked,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Migratable => write!(f, "migratable"),
+            Status::AtRisk => write!(f, "at risk"),
+            Status::Blocked => write!(f, "blocked"),
+        }
+    }
+}
+
+/// Whether a repository can be migrated to the target described by a profile, and why
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MigrationReadiness {
+    pub profile: String,
+    pub status: Status,
+    pub reasons: Vec<String>,
+}
+
+pub fn built_in_profiles() -> Vec<Profile> {
+    vec![
+        Profile {
+            name: "gei".to_string(),
+            max_file_size: Some(400 * MEBIBYTE),
+            max_repository_size: Some(40 * GIBIBYTE),
+            max_push_size: Some(2 * GIBIBYTE),
+            at_risk_ratio: 0.8,
+        },
+        Profile {
+            name: "github-com-push-limits".to_string(),
+            max_file_size: Some(100 * MEBIBYTE),
+            max_repository_size: None,
+            max_push_size: Some(2 * GIBIBYTE),
+            at_risk_ratio: 0.5,
+        },
+    ]
+}
+
+impl Profile {
+    /// Finds a profile by name, applying any overrides from a profiles file
+    pub fn find(name: &str, profiles_file: Option<&str>) -> Result<Profile, Error> {
+        let overrides = match profiles_file {
+            Some(path) => {
+                let contents = fs::read_to_string(path).map_err(|e| {
+                    Error::new(
+                        e.kind(),
+                        format!("Failed to read profiles file {}: {}", path, e),
+                    )
+                })?;
+
+                toml::from_str::<ProfilesFile>(&contents).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Failed to parse profiles file {}: {}", path, e.message()),
+                    )
+                })?
+            }
+            None => ProfilesFile::default(),
+        };
+
+        let built_in = built_in_profiles()
+            .into_iter()
+            .find(|profile| profile.name == name);
+
+        match (built_in, overrides.profiles.get(name)) {
+            (Some(profile), Some(overrides)) => profile.with_overrides(overrides),
+            (Some(profile), None) => Ok(profile),
+            (None, Some(overrides)) => Profile {
+                name: name.to_string(),
+                max_file_size: None,
+                max_repository_size: None,
+                max_push_size: None,
+                at_risk_ratio: 0.8,
+            }
+            .with_overrides(overrides),
+            (None, None) => Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "Unknown profile `{}`. The built-in profiles are: {}",
+                    name,
+                    built_in_profiles()
+                        .iter()
+                        .map(|profile| profile.name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ),
+            )),
+        }
+    }
+
+    fn with_overrides(mut self, overrides: &ProfileOverrides) -> Result<Profile, Error> {
+        let parse = |size: &Option<RawSize>| -> Result<Option<u64>, Error> {
+            size.as_ref()
+                .map(RawSize::to_u64)
+                .transpose()
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+        };
+
+        if let Some(max_file_size) = parse(&overrides.max_file_size)? {
+            self.max_file_size = Some(max_file_size);
+        }
+        if let Some(max_repository_size) = parse(&overrides.max_repository_size)? {
+            self.max_repository_size = Some(max_repository_size);
+        }
+        if let Some(max_push_size) = parse(&overrides.max_push_size)? {
+            self.max_push_size = Some(max_push_size);
+        }
+        if let Some(at_risk_ratio) = overrides.at_risk_ratio {
+            self.at_risk_ratio = at_risk_ratio;
+        }
+
+        Ok(self)
+    }
+
+    pub fn assess(&self, report: &SizerReport) -> MigrationReadiness {
+        let mut status = Status::Migratable;
+        let mut reasons = Vec::new();
+
+        // git-sizer reports uncompressed sizes, so this overestimates what is actually transferred
+        let repository_size =
+            report.unique_commit_size + report.unique_tree_size + report.unique_blob_size;
+
+        if let Some(max_file_size) = self.max_file_size {
+            let largest_file = match &report.max_blob_size_blob {
+                Some(referent) => format!("The largest file ({})", referent),
+                None => "The largest file".to_string(),
+            };
+
+            if report.max_blob_size > max_file_size {
+                status = Status::Blocked;
+                reasons.push(format!(
+                    "{} is {}, which exceeds the limit of {}",
+                    largest_file,
+                    format_bytes(report.max_blob_size),
+                    format_bytes(max_file_size)
+                ));
+            } else if self.is_close_to(report.max_blob_size, max_file_size) {
+                status = status.max(Status::AtRisk);
+                reasons.push(format!(
+                    "{} is {}, which is close to the limit of {}",
+                    largest_file,
+                    format_bytes(report.max_blob_size),
+                    format_bytes(max_file_size)
+                ));
+            }
+        }
+
+        if let Some(max_repository_size) = self.max_repository_size {
+            if repository_size > max_repository_size {
+                status = Status::Blocked;
+                reasons.push(format!(
+                    "The repository is {}, which exceeds the limit of {}",
+                    format_bytes(repository_size),
+                    format_bytes(max_repository_size)
+                ));
+            } else if self.is_close_to(repository_size, max_repository_size) {
+                status = status.max(Status::AtRisk);
+                reasons.push(format!(
+                    "The repository is {}, which is close to the limit of {}",
+                    format_bytes(repository_size),
+                    format_bytes(max_repository_size)
+                ));
+            }
+        }
+
+        if let Some(max_push_size) = self.max_push_size {
+            if repository_size > max_push_size {
+                status = status.max(Status::AtRisk);
+                reasons.push(format!(
+                    "The repository is {}, which exceeds the push limit of {}, so its history will need to be pushed in several smaller pushes",
+                    format_bytes(repository_size),
+                    format_bytes(max_push_size)
+                ));
+            }
+        }
+
+        MigrationReadiness {
+            profile: self.name.clone(),
+            status,
+            reasons,
+        }
+    }
+
+    fn is_close_to(&self, value: u64, limit: u64) -> bool {
+        value as f64 > limit as f64 * self.at_risk_ratio
+    }
+}
+
+#[test]
+fn assess_reports_small_repo_as_migratable() {
+    let profile = Profile::find("gei", None).unwrap();
+    let report = SizerReport {
+        max_blob_size: 13,
+        unique_blob_size: 13,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        profile.assess(&report),
+        MigrationReadiness {
+            profile: "gei".to_string(),
+            status: Status::Migratable,
+            reasons: vec![],
+        }
+    );
+}
+
+#[test]
+fn assess_reports_repo_with_oversized_file_as_blocked() {
+    let profile = Profile::find("github-com-push-limits", None).unwrap();
+    let report = SizerReport {
+        max_blob_size: 150 * MEBIBYTE,
+        max_blob_size_blob: Some("abc123 (refs/heads/main:video.mp4)".to_string()),
+        unique_blob_size: 150 * MEBIBYTE,
+        ..Default::default()
+    };
+
+    let readiness = profile.assess(&report);
+
+    assert_eq!(readiness.status, Status::Blocked);
+    assert_eq!(
+        readiness.reasons,
+        vec!["The largest file (abc123 (refs/heads/main:video.mp4)) is 150 MiB, which exceeds the limit of 100 MiB"]
+    );
+}
+
+#[test]
+fn assess_reports_repo_exceeding_push_limit_as_at_risk() {
+    let profile = Profile::find("gei", None).unwrap();
+    let report = SizerReport {
+        max_blob_size: MEBIBYTE,
+        unique_blob_size: 3 * GIBIBYTE,
+        ..Default::default()
+    };
+
+    let readiness = profile.assess(&report);
+
+    assert_eq!(readiness.status, Status::AtRisk);
+    assert_eq!(readiness.reasons.len(), 1);
+    assert!(readiness.reasons[0].contains("exceeds the push limit of 2.00 GiB"));
+}
+
+#[test]
+fn find_applies_overrides_from_profiles_file() {
+    let mut profiles_file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(
+        &mut profiles_file,
+        b"[profiles.gei]\nmax_file_size = \"50 MiB\"\n\n[profiles.our-ghes]\nmax_repository_size = \"10 GiB\"\n",
+    )
+    .unwrap();
+    let path = profiles_file.path().to_str().unwrap();
+
+    let gei = Profile::find("gei", Some(path)).unwrap();
+    assert_eq!(gei.max_file_size, Some(50 * MEBIBYTE));
+    assert_eq!(gei.max_repository_size, Some(40 * GIBIBYTE));
+
+    let our_ghes = Profile::find("our-ghes", Some(path)).unwrap();
+    assert_eq!(our_ghes.max_file_size, None);
+    assert_eq!(our_ghes.max_repository_size, Some(10 * GIBIBYTE));
+}
+
+#[test]
+fn find_errors_on_unknown_profile() {
+    let error = Profile::find("nope", None).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Unknown profile `nope`. The built-in profiles are: gei, github-com-push-limits"
+    );
+}
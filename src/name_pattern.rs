@@ -0,0 +1,107 @@
+use regex::Regex;
+use std::fmt;
+
+/// A pattern for matching repository names, written either as a glob, e.g. `api-*`, which is
+/// matched case-insensitively against the whole name, or as a regular expression between
+/// slashes, e.g. `/^api-(v1|v2)$/`
+#[derive(Clone, Debug)]
+pub struct NamePattern {
+    source: String,
+    regex: Regex,
+}
+
+impl NamePattern {
+    pub fn parse(pattern: &str) -> Result<NamePattern, String> {
+        let regex = match pattern
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+        {
+            Some(regex) => Regex::new(regex),
+            None => Regex::new(&glob_to_regex(pattern)),
+        }
+        .map_err(|e| format!("`{}` isn't a valid pattern: {}", pattern, e))?;
+
+        Ok(NamePattern {
+            source: pattern.to_string(),
+            regex,
+        })
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("(?i)^");
+    let mut characters = glob.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push('[');
+
+                if characters.next_if_eq(&'!').is_some() {
+                    regex.push('^');
+                }
+
+                for character in characters.by_ref() {
+                    if character == ']' {
+                        break;
+                    }
+
+                    if character == '\\' || character == '[' {
+                        regex.push('\\');
+                    }
+
+                    regex.push(character);
+                }
+
+                regex.push(']');
+            }
+            _ => regex.push_str(&regex::escape(&character.to_string())),
+        }
+    }
+
+    regex.push('$');
+    regex
+}
+
+#[test]
+fn glob_patterns_match_whole_name_case_insensitively() {
+    let pattern = NamePattern::parse("api-*").unwrap();
+
+    assert!(pattern.is_match("api-gateway"));
+    assert!(pattern.is_match("API-Gateway"));
+    assert!(!pattern.is_match("legacy-api-gateway"));
+
+    let pattern = NamePattern::parse("service-[!0-9]?.rs").unwrap();
+
+    assert!(pattern.is_match("service-ab.rs"));
+    assert!(!pattern.is_match("service-1b.rs"));
+    assert!(!pattern.is_match("service-abrs"));
+}
+
+#[test]
+fn regex_patterns_are_written_between_slashes() {
+    let pattern = NamePattern::parse("/-(archive|old)$/").unwrap();
+
+    assert!(pattern.is_match("website-old"));
+    assert!(!pattern.is_match("website"));
+    assert_eq!(pattern.to_string(), "/-(archive|old)$/");
+}
+
+#[test]
+fn invalid_regex_patterns_are_rejected() {
+    assert!(NamePattern::parse("/(unclosed/")
+        .unwrap_err()
+        .starts_with("`/(unclosed/` isn't a valid pattern"));
+}
@@ -0,0 +1,309 @@
+use std::fs;
+use std::io::{Error, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::enums::OutputFormat;
+use crate::generate_script::{ensure_unique_output_paths, output_filename};
+use crate::github_repository_lister::GitHubRepositoryLister;
+use crate::migration_readiness::Profile;
+use crate::report::Report;
+use crate::repository_filter::RepositoryFilter;
+use crate::repository_sizer::RepositorySizer;
+use crate::repository_source::RepositorySource;
+
+#[cfg(test)]
+use crate::github_repository_lister::{MockGitHubRepositoryLister, Repository};
+#[cfg(test)]
+use crate::repository_sizer::MockRepositorySizer;
+#[cfg(test)]
+use crate::sizer_report::SizerReport;
+
+/// The outcome of sizing a set of repositories
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<(String, String)>,
+    /// Repositories which were sized successfully, but exceeded one or more policy limits
+    pub violated_policy: Vec<String>,
+}
+
+impl Summary {
+    pub fn total(&self) -> usize {
+        self.succeeded.len() + self.failed.len()
+    }
+
+    pub fn write_to(&self, stderr: &mut impl Write, description: &str) -> Result<(), Error> {
+        writeln!(
+            stderr,
+            "Finished sizing {} {}: {} succeeded, {} failed",
+            self.total(),
+            description,
+            self.succeeded.len(),
+            self.failed.len()
+        )?;
+
+        for (name, _) in &self.failed {
+            writeln!(stderr, "* {}", name)?;
+        }
+
+        if !self.violated_policy.is_empty() {
+            writeln!(
+                stderr,
+                "{} repositories exceeded policy limits:",
+                self.violated_policy.len()
+            )?;
+
+            for name in &self.violated_policy {
+                writeln!(stderr, "* {}", name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A repository to size, and the path to write its results to
+pub struct Target {
+    pub name: String,
+    pub output_path: PathBuf,
+}
+
+/// Each target's name and output path, to check that no two targets share an output path
+pub fn target_outputs(targets: &[Target]) -> Vec<(String, PathBuf)> {
+    targets
+        .iter()
+        .map(|target| (target.name.clone(), target.output_path.clone()))
+        .collect()
+}
+
+/// Sizes a set of repositories, working on up to `jobs` of them at the same time, and writes
+/// each one's report to its output path. Progress lines describe each target as a `noun`, e.g.
+/// `repo` or `gist`.
+pub fn size_all(
+    targets: &[Target],
+    noun: &str,
+    jobs: usize,
+    output_format: OutputFormat,
+    size: &(impl Fn(&Target) -> Result<Report, Error> + Sync),
+    stderr: &mut (impl Write + Send),
+) -> Summary {
+    let targets_count = targets.len();
+    let next_index = AtomicUsize::new(0);
+    let completed_count = AtomicUsize::new(0);
+    let summary = Mutex::new(Summary::default());
+    let stderr = Mutex::new(stderr);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1).min(targets_count.max(1)) {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::SeqCst);
+
+                let Some(target) = targets.get(index) else {
+                    break;
+                };
+
+                let result = size(target).and_then(|report| {
+                    fs::write(&target.output_path, report.render(output_format.to_owned()))?;
+                    Ok(report)
+                });
+
+                let completed = completed_count.fetch_add(1, Ordering::SeqCst) + 1;
+                let mut stderr = stderr.lock().unwrap();
+                let mut summary = summary.lock().unwrap();
+
+                match result {
+                    Ok(report) => {
+                        let violations_count = report.violation_count();
+
+                        if violations_count > 0 {
+                            let _ = writeln!(
+                                stderr,
+                                "Sized {} {} ({}/{}), which exceeds {} policy limits",
+                                noun, target.name, completed, targets_count, violations_count
+                            );
+                            summary.violated_policy.push(target.name.clone());
+                        } else {
+                            let _ = writeln!(
+                                stderr,
+                                "Sized {} {} ({}/{})",
+                                noun, target.name, completed, targets_count
+                            );
+                        }
+
+                        summary.succeeded.push(target.name.clone());
+                    }
+                    Err(e) => {
+                        let _ = writeln!(
+                            stderr,
+                            "Failed to size {} {} ({}/{}): {}",
+                            noun,
+                            target.name,
+                            completed,
+                            targets_count,
+                            e.to_string().trim_end()
+                        );
+                        summary.failed.push((target.name.clone(), e.to_string()));
+                    }
+                }
+            });
+        }
+    });
+
+    summary.into_inner().unwrap()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn call(
+    repository_source: &RepositorySource,
+    jobs: usize,
+    output_format: OutputFormat,
+    output_directory: &str,
+    output_filename_template: &str,
+    profile: Option<&Profile>,
+    github_repository_lister: &impl GitHubRepositoryLister,
+    repository_sizer: &(impl RepositorySizer + Sync),
+    stderr: &mut (impl Write + Send),
+) -> Result<Summary, Error> {
+    let repositories = repository_source.list(
+        &RepositoryFilter::default(),
+        github_repository_lister,
+        stderr,
+    )?;
+
+    let targets: Vec<Target> = repositories
+        .iter()
+        .map(|repository| Target {
+            name: repository.nwo(),
+            output_path: PathBuf::from(output_directory).join(output_filename(
+                output_filename_template,
+                &repository.owner,
+                &repository.name,
+            )),
+        })
+        .collect();
+
+    ensure_unique_output_paths(&target_outputs(&targets))?;
+
+    writeln!(
+        stderr,
+        "Sizing {} repositories {} using {} jobs",
+        repositories.len(),
+        repository_source,
+        jobs
+    )?;
+
+    fs::create_dir_all(output_directory)?;
+
+    let summary = size_all(
+        &targets,
+        "repo",
+        jobs,
+        output_format,
+        &|target| Ok(repository_sizer.call(&target.name)?.check(None, profile)),
+        stderr,
+    );
+
+    summary.write_to(stderr, &format!("repositories {}", repository_source))?;
+
+    Ok(summary)
+}
+
+#[test]
+fn org_writes_output_for_each_repo_and_tallies_results() {
+    let mut lister_mock = MockGitHubRepositoryLister::new();
+
+    lister_mock.expect_call().returning(|_| {
+        Ok(vec![
+            Repository::new("github", "first-repo"),
+            Repository::new("github", "second-repo"),
+            Repository::new("github", "broken-repo"),
+        ])
+    });
+
+    let mut sizer_mock = MockRepositorySizer::new();
+
+    sizer_mock
+        .expect_call()
+        .returning(|nwo| match nwo {
+            "github/broken-repo" => Err(Error::other("clone failed")),
+            _ => Ok(Report::new(SizerReport {
+                unique_commit_count: 1,
+                ..Default::default()
+            })),
+        })
+        .times(3);
+
+    let output_directory = tempfile::tempdir().unwrap();
+    let mut stderr = Vec::new();
+
+    let summary = call(
+        &RepositorySource::Owners(vec!["github".to_string()]),
+        2,
+        OutputFormat::Text,
+        output_directory.path().to_str().unwrap(),
+        "${owner}-${repository}.txt",
+        None,
+        &lister_mock,
+        &sizer_mock,
+        &mut stderr,
+    )
+    .unwrap();
+
+    assert_eq!(summary.total(), 3);
+    assert_eq!(summary.succeeded.len(), 2);
+    assert_eq!(
+        summary.failed,
+        vec![("github/broken-repo".to_string(), "clone failed".to_string())]
+    );
+
+    assert!(
+        fs::read_to_string(output_directory.path().join("github-first-repo.txt"))
+            .unwrap()
+            .contains("|   * Count                    |     1     |")
+    );
+    assert!(!output_directory
+        .path()
+        .join("github-broken-repo.txt")
+        .exists());
+
+    let stderr = String::from_utf8_lossy(&stderr);
+    assert!(stderr.starts_with("Sizing 3 repositories owned by github using 2 jobs\n"));
+    assert!(stderr.ends_with(
+        "Finished sizing 3 repositories owned by github: 2 succeeded, 1 failed\n* github/broken-repo\n"
+    ));
+}
+
+#[test]
+fn org_refuses_to_save_same_named_repos_from_different_owners_to_one_file() {
+    let mut lister_mock = MockGitHubRepositoryLister::new();
+
+    lister_mock
+        .expect_call()
+        .returning(|owner| Ok(vec![Repository::new(owner, "api")]));
+
+    let mut sizer_mock = MockRepositorySizer::new();
+
+    sizer_mock.expect_call().never();
+
+    let output_directory = tempfile::tempdir().unwrap();
+
+    let error = call(
+        &RepositorySource::Owners(vec!["org-a".to_string(), "org-b".to_string()]),
+        2,
+        OutputFormat::Text,
+        output_directory.path().to_str().unwrap(),
+        "${repository}.txt",
+        None,
+        &lister_mock,
+        &sizer_mock,
+        &mut Vec::new(),
+    )
+    .unwrap_err();
+
+    assert!(error
+        .to_string()
+        .starts_with("The results for org-a/api and org-b/api would both be saved to "));
+}
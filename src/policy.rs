@@ -0,0 +1,297 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use crate::sizer_report::{format_value, Metric, SizerReport, Unit};
+
+/// The exit code used when a repository exceeds one or more of a policy's limits. This is
+/// distinct from the codes used for failures, including the `2` which clap uses for invalid
+/// arguments, so scripts can tell a violation apart from an error.
+pub const POLICY_VIOLATED: exitcode::ExitCode = 3;
+
+/// Limits that a repository's `git-sizer` metrics must not exceed, loaded from a TOML file, e.g.
+///
+/// ```toml
+/// max_level_of_concern = 5
+///
+/// [limits]
+/// max_blob_size = "50 MiB"
+/// unique_blob_size = "5 GiB"
+/// max_tree_entries = 2500
+/// ```
+///
+/// or a YAML file with the same fields, if its name ends in `.yaml` or `.yml`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// The maximum level of concern, measured in `git-sizer`'s stars, that any metric may reach
+    pub max_level_of_concern: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_limits")]
+    pub limits: BTreeMap<Metric, u64>,
+}
+
+/// A limit in a policy which a repository exceeds
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    pub metric: String,
+    pub value: u64,
+    pub limit: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referent: Option<String>,
+}
+
+const LEVEL_OF_CONCERN: &str = "level_of_concern";
+
+impl Policy {
+    pub fn from_file(path: &str) -> Result<Policy, Error> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!("Failed to read policy file {}: {}", path, e),
+            )
+        })?;
+
+        let is_yaml = Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension == "yaml" || extension == "yml");
+        let policy = if is_yaml {
+            Policy::from_yaml(&contents)
+        } else {
+            Policy::from_toml(&contents)
+        };
+
+        policy.map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!("Failed to parse policy file {}: {}", path, e),
+            )
+        })
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Policy, Error> {
+        toml::from_str(toml).map_err(|e| Error::new(ErrorKind::InvalidData, e.message()))
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Policy, Error> {
+        serde_yaml::from_str(yaml).map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))
+    }
+
+    pub fn evaluate(&self, report: &SizerReport) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = self
+            .limits
+            .iter()
+            .filter(|(metric, limit)| report.value(**metric) > **limit)
+            .map(|(metric, limit)| Violation {
+                metric: metric.key().to_string(),
+                value: report.value(*metric),
+                limit: *limit,
+                referent: report.referent(*metric).map(|r| r.to_string()),
+            })
+            .collect();
+
+        if let Some(max_level_of_concern) = self.max_level_of_concern {
+            let level_of_concern = report.max_level_of_concern() as u64;
+
+            if level_of_concern > max_level_of_concern {
+                violations.push(Violation {
+                    metric: LEVEL_OF_CONCERN.to_string(),
+                    value: level_of_concern,
+                    limit: max_level_of_concern,
+                    referent: None,
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unit = Metric::from_key(&self.metric)
+            .map(|metric| metric.unit())
+            .unwrap_or(Unit::Count);
+
+        write!(
+            f,
+            "{} is {}, which exceeds the limit of {}",
+            self.metric,
+            format_human(self.value, unit),
+            format_human(self.limit, unit)
+        )?;
+
+        if let Some(referent) = &self.referent {
+            write!(f, " ({})", referent)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn format_human(value: u64, unit: Unit) -> String {
+    let (numeral, unit) = format_value(value, unit);
+    format!("{} {}", numeral, unit).trim_end().to_string()
+}
+
+/// Parses a human-readable size or count, e.g. `1500`, `250k`, `50 MiB` or `2GB`
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let split_at = size
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split_at);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("`{}` is not a valid size", size))?;
+
+    let multiplier: f64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(format!("`{}` has an unknown unit `{}`", size, unit.trim())),
+    };
+
+    Ok((number * multiplier).round() as u64)
+}
+
+/// A size or count in a configuration file, written either as a number or as a string with a unit
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum RawSize {
+    Number(u64),
+    Text(String),
+}
+
+impl RawSize {
+    pub(crate) fn to_u64(&self) -> Result<u64, String> {
+        match self {
+            RawSize::Number(size) => Ok(*size),
+            RawSize::Text(size) => parse_size(size),
+        }
+    }
+}
+
+fn deserialize_limits<'de, D>(deserializer: D) -> Result<BTreeMap<Metric, u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<Metric, RawSize>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(metric, limit)| {
+            limit
+                .to_u64()
+                .map(|limit| (metric, limit))
+                .map_err(D::Error::custom)
+        })
+        .collect()
+}
+
+#[test]
+fn from_toml_parses_limits_and_level_of_concern() {
+    let policy = Policy::from_toml(
+        "max_level_of_concern = 5\n\n[limits]\nmax_blob_size = \"50 MiB\"\nmax_tree_entries = 2500\n",
+    )
+    .unwrap();
+
+    assert_eq!(policy.max_level_of_concern, Some(5));
+    assert_eq!(policy.limits.get(&Metric::MaxBlobSize), Some(&52_428_800));
+    assert_eq!(policy.limits.get(&Metric::MaxTreeEntries), Some(&2500));
+}
+
+#[test]
+fn from_toml_errors_on_unknown_metric() {
+    let error = Policy::from_toml("[limits]\nmax_banana_size = 1\n").unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(error.to_string().contains("max_banana_size"));
+}
+
+#[test]
+fn evaluate_reports_exceeded_limits() {
+    let policy = Policy::from_toml(
+        "max_level_of_concern = 1\n\n[limits]\nmax_blob_size = \"10 MiB\"\nreference_count = 100\n",
+    )
+    .unwrap();
+    let report = SizerReport {
+        max_blob_size: 25_000_000,
+        max_blob_size_blob: Some("abc123 (refs/heads/main:big.bin)".to_string()),
+        reference_count: 10,
+        ..Default::default()
+    };
+
+    let violations = policy.evaluate(&report);
+
+    assert_eq!(
+        violations,
+        vec![
+            Violation {
+                metric: "max_blob_size".to_string(),
+                value: 25_000_000,
+                limit: 10_485_760,
+                referent: Some("abc123 (refs/heads/main:big.bin)".to_string()),
+            },
+            Violation {
+                metric: "level_of_concern".to_string(),
+                value: 2,
+                limit: 1,
+                referent: None,
+            },
+        ]
+    );
+    assert_eq!(
+        violations[0].to_string(),
+        "max_blob_size is 23.8 MiB, which exceeds the limit of 10.0 MiB (abc123 (refs/heads/main:big.bin))"
+    );
+}
+
+#[test]
+fn parse_size_accepts_decimal_and_binary_units() {
+    assert_eq!(parse_size("1500"), Ok(1500));
+    assert_eq!(parse_size("250k"), Ok(250_000));
+    assert_eq!(parse_size("2GB"), Ok(2_000_000_000));
+    assert_eq!(parse_size("1.5 KiB"), Ok(1536));
+    assert!(parse_size("lots").is_err());
+    assert!(parse_size("5 furlongs").is_err());
+}
+
+#[test]
+fn from_yaml_parses_the_same_fields_as_toml() {
+    let policy = Policy::from_yaml(
+        "max_level_of_concern: 5\nlimits:\n  max_blob_size: 50 MiB\n  max_tree_entries: 2500\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        policy,
+        Policy::from_toml(
+            "max_level_of_concern = 5\n\n[limits]\nmax_blob_size = \"50 MiB\"\nmax_tree_entries = 2500\n",
+        )
+        .unwrap()
+    );
+    assert!(Policy::from_yaml("limits:\n  max_banana_size: 1\n")
+        .unwrap_err()
+        .to_string()
+        .contains("max_banana_size"));
+}
+
+#[test]
+fn from_file_reads_yaml_files_by_their_extension() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("policy.yml");
+    fs::write(&path, "limits:\n  max_tree_entries: 2500\n").unwrap();
+
+    let policy = Policy::from_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(policy.limits.get(&Metric::MaxTreeEntries), Some(&2500));
+}
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::history::History;
+use crate::sizer_report::format_bytes;
+
+/// Blobs at least this big are considered large enough to act on
+pub const LARGE_BLOB_SIZE: u64 = 1024 * 1024;
+
+/// The most recommendations of each kind to make, largest savings first
+const MAX_RECOMMENDATIONS: usize = 10;
+
+/// A suggested pattern to track with Git LFS or path to remove from history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub pattern: String,
+    /// The number of large blobs matched by the pattern
+    pub large_blob_count: usize,
+    /// The total uncompressed size of the blobs which would be removed from the repository's
+    /// history, in bytes
+    pub estimated_savings: u64,
+}
+
+impl Recommendation {
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}: {} large blobs, saving about {}",
+            self.pattern,
+            self.large_blob_count,
+            format_bytes(self.estimated_savings)
+        )
+    }
+}
+
+/// Suggestions for making a repository smaller by moving large files to Git LFS or removing
+/// them from history
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recommendations {
+    pub lfs_patterns: Vec<Recommendation>,
+    /// A `git lfs migrate import` command which tracks all of the `lfs_patterns`
+    pub lfs_command: Option<String>,
+    pub path_removals: Vec<Recommendation>,
+    /// A `git filter-repo` command which removes all of the `path_removals`
+    pub filter_repo_command: Option<String>,
+}
+
+impl Recommendations {
+    pub fn to_text(&self) -> String {
+        if self.lfs_patterns.is_empty() && self.path_removals.is_empty() {
+            return format!(
+                "There are no blobs of {} or more, so there is nothing to recommend\n",
+                format_bytes(LARGE_BLOB_SIZE)
+            );
+        }
+
+        let mut output = String::new();
+
+        if let Some(lfs_command) = &self.lfs_command {
+            output.push_str("Track these file types with Git LFS:\n");
+
+            for recommendation in &self.lfs_patterns {
+                output.push_str(&format!("* {}\n", recommendation.to_text()));
+            }
+
+            output.push_str(&format!("Run: {}\n", lfs_command));
+        }
+
+        if let Some(filter_repo_command) = &self.filter_repo_command {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+
+            output.push_str("Or remove these paths from history:\n");
+
+            for recommendation in &self.path_removals {
+                output.push_str(&format!("* {}\n", recommendation.to_text()));
+            }
+
+            output.push_str(&format!("Run: {}\n", filter_repo_command));
+        }
+
+        output
+    }
+}
+
+/// Groups the large blobs in a repository's history by file extension and by directory to
+/// recommend Git LFS tracking patterns and paths to remove from history. The savings for each
+/// recommendation count every blob, large or not, which is only ever stored at matching paths.
+pub fn recommend(history: &History, large_blob_size: u64) -> Recommendations {
+    let mut blob_paths: HashMap<&str, Vec<&str>> = HashMap::new();
+
+    for commit in &history.commits {
+        for change in &commit.changes {
+            let paths = blob_paths.entry(&change.blob).or_default();
+
+            if !paths.contains(&change.path.as_str()) {
+                paths.push(&change.path);
+            }
+        }
+    }
+
+    let mut lfs_patterns: HashMap<String, usize> = HashMap::new();
+    let mut path_removals: HashMap<String, usize> = HashMap::new();
+
+    for (blob, paths) in &blob_paths {
+        if history.blob_size(blob) < large_blob_size {
+            continue;
+        }
+
+        if let Some(extension) = extension(paths[0]) {
+            *lfs_patterns.entry(format!("*.{}", extension)).or_default() += 1;
+        }
+
+        let path_removal = match paths[0].rsplit_once('/') {
+            Some((directory, _)) => format!("{}/", directory),
+            None => paths[0].to_string(),
+        };
+        *path_removals.entry(path_removal).or_default() += 1;
+    }
+
+    let savings = |matches: &dyn Fn(&str) -> bool| -> u64 {
+        blob_paths
+            .iter()
+            .filter(|(_, paths)| paths.iter().all(|path| matches(path)))
+            .map(|(blob, _)| history.blob_size(blob))
+            .sum()
+    };
+
+    let lfs_patterns = top_recommendations(lfs_patterns, |pattern| {
+        let suffix = &pattern[1..];
+        savings(&|path| path.ends_with(suffix))
+    });
+
+    let path_removals = top_recommendations(path_removals, |pattern| {
+        if pattern.ends_with('/') {
+            savings(&|path| path.starts_with(pattern))
+        } else {
+            savings(&|path| path == pattern)
+        }
+    });
+
+    Recommendations {
+        lfs_command: (!lfs_patterns.is_empty()).then(|| {
+            format!(
+                "git lfs migrate import --everything --include={}",
+                quote(
+                    &lfs_patterns
+                        .iter()
+                        .map(|recommendation| recommendation.pattern.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(",")
+                )
+            )
+        }),
+        filter_repo_command: (!path_removals.is_empty()).then(|| {
+            format!(
+                "git filter-repo --invert-paths{}",
+                path_removals
+                    .iter()
+                    .map(|recommendation| format!(" --path {}", quote(&recommendation.pattern)))
+                    .collect::<String>()
+            )
+        }),
+        lfs_patterns,
+        path_removals,
+    }
+}
+
+/// The extension of the file at a path, if it has one, e.g. `mp4` for `assets/intro.mp4`
+fn extension(path: &str) -> Option<&str> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && !extension.is_empty() => Some(extension),
+        _ => None,
+    }
+}
+
+fn top_recommendations(
+    large_blob_counts: HashMap<String, usize>,
+    estimate_savings: impl Fn(&str) -> u64,
+) -> Vec<Recommendation> {
+    let mut recommendations: Vec<Recommendation> = large_blob_counts
+        .into_iter()
+        .map(|(pattern, large_blob_count)| Recommendation {
+            estimated_savings: estimate_savings(&pattern),
+            pattern,
+            large_blob_count,
+        })
+        .collect();
+
+    recommendations.sort_by(|a, b| {
+        b.estimated_savings
+            .cmp(&a.estimated_savings)
+            .then_with(|| a.pattern.cmp(&b.pattern))
+    });
+    recommendations.truncate(MAX_RECOMMENDATIONS);
+    recommendations
+}
+
+/// Quotes an argument for a POSIX shell, if it contains any special characters
+fn quote(argument: &str) -> String {
+    if argument
+        .chars()
+        .all(|character| character.is_ascii_alphanumeric() || "-_./,".contains(character))
+    {
+        argument.to_string()
+    } else {
+        format!("'{}'", argument.replace('\'', "'\\''"))
+    }
+}
+
+#[test]
+fn recommend_groups_large_blobs_by_extension_and_directory() {
+    let directory = tempfile::tempdir().unwrap();
+    let history = History::read(
+        &crate::history::example_repository(directory.path()),
+        &crate::ref_selection::RefSelection::default(),
+    )
+    .unwrap();
+
+    let recommendations = recommend(&history, 1000);
+
+    assert_eq!(
+        recommendations.lfs_patterns,
+        vec![
+            Recommendation {
+                pattern: "*.mp4".to_string(),
+                large_blob_count: 1,
+                estimated_savings: 5000,
+            },
+            Recommendation {
+                pattern: "*.png".to_string(),
+                large_blob_count: 1,
+                estimated_savings: 2000,
+            },
+        ]
+    );
+    assert_eq!(
+        recommendations.lfs_command.as_deref(),
+        Some("git lfs migrate import --everything --include='*.mp4,*.png'")
+    );
+    assert_eq!(
+        recommendations.path_removals,
+        vec![Recommendation {
+            pattern: "assets/".to_string(),
+            large_blob_count: 2,
+            estimated_savings: 7000,
+        }]
+    );
+    assert_eq!(
+        recommendations.filter_repo_command.as_deref(),
+        Some("git filter-repo --invert-paths --path assets/")
+    );
+}
+
+#[test]
+fn recommend_has_nothing_to_suggest_without_large_blobs() {
+    let directory = tempfile::tempdir().unwrap();
+    let history = History::read(
+        &crate::history::example_repository(directory.path()),
+        &crate::ref_selection::RefSelection::default(),
+    )
+    .unwrap();
+
+    let recommendations = recommend(&history, LARGE_BLOB_SIZE);
+
+    assert_eq!(recommendations, Recommendations::default());
+    assert_eq!(
+        recommendations.to_text(),
+        "There are no blobs of 1.00 MiB or more, so there is nothing to recommend\n"
+    );
+}
+
+#[test]
+fn extension_ignores_dotfiles_and_directories() {
+    assert_eq!(extension("assets/intro.MP4"), Some("MP4"));
+    assert_eq!(extension("archive.tar.gz"), Some("gz"));
+    assert_eq!(extension(".gitignore"), None);
+    assert_eq!(extension("v1.2/Makefile"), None);
+}
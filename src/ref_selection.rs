@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+/// Which parts of a repository to size, rather than every ref and all of history
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RefSelection {
+    /// If any are set, only size refs whose names start with one of these prefixes, e.g.
+    /// `refs/heads/main` or `refs/tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refs: Vec<String>,
+    /// Don't size refs whose names start with any of these prefixes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_refs: Vec<String>,
+    /// Only size history from on or after this date, in `YYYY-MM-DD` format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+impl RefSelection {
+    /// Whether the selection includes every ref and all of history
+    pub fn is_empty(&self) -> bool {
+        self.refs.is_empty() && self.exclude_refs.is_empty() && self.since.is_none()
+    }
+
+    /// Whether the selection restricts which refs are sized
+    pub fn filters_refs(&self) -> bool {
+        !self.refs.is_empty() || !self.exclude_refs.is_empty()
+    }
+
+    /// Whether a ref is included in the selection. Prefixes match whole path components, so
+    /// `refs/heads/main` matches `refs/heads/main` and `refs/heads/main/fix`, but not
+    /// `refs/heads/maintenance`.
+    pub fn matches(&self, refname: &str) -> bool {
+        let matches_prefix = |prefix: &String| {
+            let prefix = prefix.trim_end_matches('/');
+
+            refname == prefix
+                || refname
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        };
+
+        (self.refs.is_empty() || self.refs.iter().any(matches_prefix))
+            && !self.exclude_refs.iter().any(matches_prefix)
+    }
+
+    /// The arguments which make `git-sizer` size the same refs
+    pub fn sizer_arguments(&self) -> Vec<String> {
+        self.refs
+            .iter()
+            .map(|prefix| format!("--include={}", prefix))
+            .chain(
+                self.exclude_refs
+                    .iter()
+                    .map(|prefix| format!("--exclude={}", prefix)),
+            )
+            .collect()
+    }
+
+    /// The arguments which make the `repo` command use the same selection, for generated scripts
+    pub fn repo_arguments(&self) -> Vec<String> {
+        let mut arguments = Vec::new();
+
+        for prefix in &self.refs {
+            arguments.push("--refs".to_string());
+            arguments.push(prefix.to_owned());
+        }
+
+        for prefix in &self.exclude_refs {
+            arguments.push("--exclude-refs".to_string());
+            arguments.push(prefix.to_owned());
+        }
+
+        if let Some(since) = &self.since {
+            arguments.push("--since".to_string());
+            arguments.push(since.to_owned());
+        }
+
+        arguments
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut parts = Vec::new();
+
+        if self.refs.is_empty() {
+            parts.push("all refs".to_string());
+        } else {
+            parts.push(format!("refs under {}", self.refs.join(", ")));
+        }
+
+        if !self.exclude_refs.is_empty() {
+            parts.push(format!("excluding {}", self.exclude_refs.join(", ")));
+        }
+
+        if let Some(since) = &self.since {
+            parts.push(format!("with history since {}", since));
+        }
+
+        format!("Sized {}\n", parts.join(", "))
+    }
+}
+
+#[test]
+fn matches_whole_path_components() {
+    let selection = RefSelection {
+        refs: vec!["refs/heads/main".to_string(), "refs/tags/".to_string()],
+        exclude_refs: vec!["refs/tags/nightly".to_string()],
+        ..Default::default()
+    };
+
+    assert!(selection.matches("refs/heads/main"));
+    assert!(selection.matches("refs/heads/main/fix"));
+    assert!(!selection.matches("refs/heads/maintenance"));
+    assert!(selection.matches("refs/tags/v1.0"));
+    assert!(!selection.matches("refs/tags/nightly"));
+    assert!(!selection.matches("refs/tags/nightly/2024-01-01"));
+    assert!(RefSelection::default().matches("refs/pull/1/head"));
+}
+
+#[test]
+fn selection_is_converted_to_arguments_and_described() {
+    let selection = RefSelection {
+        refs: vec!["refs/heads".to_string()],
+        exclude_refs: vec!["refs/heads/archive".to_string()],
+        since: Some("2018-01-01".to_string()),
+    };
+
+    assert_eq!(
+        selection.sizer_arguments(),
+        vec!["--include=refs/heads", "--exclude=refs/heads/archive"]
+    );
+    assert_eq!(
+        selection.repo_arguments(),
+        vec![
+            "--refs",
+            "refs/heads",
+            "--exclude-refs",
+            "refs/heads/archive",
+            "--since",
+            "2018-01-01"
+        ]
+    );
+    assert_eq!(
+        selection.to_text(),
+        "Sized refs under refs/heads, excluding refs/heads/archive, with history since 2018-01-01\n"
+    );
+}
@@ -0,0 +1,407 @@
+use serde::{Deserialize, Serialize};
+use std::io::{Error, ErrorKind};
+
+use crate::additional_refs::AdditionalRefs;
+use crate::breakdown::{self, PathSize};
+use crate::enums::OutputFormat;
+use crate::growth::Growth;
+use crate::lfs::LfsObjects;
+#[cfg(test)]
+use crate::migration_readiness::Status;
+use crate::migration_readiness::{MigrationReadiness, Profile};
+use crate::policy::{Policy, Violation};
+use crate::recommendations::Recommendations;
+use crate::ref_selection::RefSelection;
+use crate::sizer_report::{to_json_string, SizerReport};
+use crate::top_blobs::LargeBlob;
+use crate::wiki::Wiki;
+
+/// Everything gh-sizer reports about a repository: `git-sizer`'s metrics, plus any
+/// additional analysis that was requested. In JSON output, the additional analysis is
+/// included alongside `git-sizer`'s own fields.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    #[serde(flatten)]
+    pub sizer_report: SizerReport,
+    /// Which refs and how much history were sized, if not everything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<RefSelection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_violations: Option<Vec<Violation>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migration_readiness: Option<MigrationReadiness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_blobs: Option<Vec<LargeBlob>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recommendations: Option<Recommendations>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<Vec<PathSize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub growth: Option<Growth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additional_refs: Option<AdditionalRefs>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lfs: Option<LfsObjects>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wiki: Option<Wiki>,
+}
+
+/// A report for one of several repositories sized together, identified by the repository's name
+#[derive(Serialize)]
+struct NamedReport<'a> {
+    repository: &'a str,
+    #[serde(flatten)]
+    report: &'a Report,
+}
+
+impl Report {
+    pub fn new(sizer_report: SizerReport) -> Report {
+        Report {
+            sizer_report,
+            ..Default::default()
+        }
+    }
+
+    /// Builds a report, checking the repository against a policy and a migration target
+    /// profile, if provided
+    pub fn with_checks(
+        sizer_report: SizerReport,
+        policy: Option<&Policy>,
+        profile: Option<&Profile>,
+    ) -> Report {
+        Report::new(sizer_report).check(policy, profile)
+    }
+
+    /// Checks the repository, and its wiki if it was sized, against a policy and a migration
+    /// target profile, if provided
+    pub fn check(self, policy: Option<&Policy>, profile: Option<&Profile>) -> Report {
+        Report {
+            policy_violations: policy.map(|policy| policy.evaluate(&self.sizer_report)),
+            migration_readiness: profile.map(|profile| profile.assess(&self.sizer_report)),
+            wiki: self.wiki.map(|wiki| Wiki {
+                report: wiki
+                    .report
+                    .map(|report| Box::new(report.check(policy, profile))),
+                ..wiki
+            }),
+            ..self
+        }
+    }
+
+    /// The number of policy limits the repository, and its wiki if it was sized, exceed
+    pub fn violation_count(&self) -> usize {
+        let wiki_violation_count = self
+            .wiki
+            .as_ref()
+            .and_then(|wiki| wiki.report.as_ref())
+            .map_or(0, |report| report.violation_count());
+
+        self.policy_violations
+            .as_ref()
+            .map_or(0, |violations| violations.len())
+            + wiki_violation_count
+    }
+
+    /// Parses a report previously rendered in either format, e.g. by a generated script
+    pub fn parse(contents: &str) -> Result<Report, Error> {
+        let contents = contents.trim_start();
+
+        if contents.is_empty() {
+            Err(Error::new(ErrorKind::InvalidData, "The file is empty"))
+        } else if contents.starts_with('{') {
+            // Every field of `git-sizer`'s report is optional when parsing, so check for one
+            // which every report includes, to reject other JSON, e.g. an `--estimate` result
+            let value: serde_json::Value = serde_json::from_str(contents)?;
+
+            if value.get("unique_commit_count").is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "The file isn't a git-sizer report, as it doesn't include `unique_commit_count`",
+                ));
+            }
+
+            Ok(serde_json::from_value(value)?)
+        } else {
+            SizerReport::from_text(contents).map(Report::new)
+        }
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_text(),
+            OutputFormat::Json => to_json_string(self),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut output = self.sizer_report.to_text();
+
+        if let Some(selection) = &self.selection {
+            output.push_str(&format!("\n{}", selection.to_text()));
+        }
+
+        if let Some(policy_violations) = &self.policy_violations {
+            output.push('\n');
+
+            if policy_violations.is_empty() {
+                output.push_str("No policy limits were exceeded\n");
+            } else {
+                output.push_str("Policy violations:\n");
+
+                for violation in policy_violations {
+                    output.push_str(&format!("* {}\n", violation));
+                }
+            }
+        }
+
+        if let Some(migration_readiness) = &self.migration_readiness {
+            output.push_str(&format!(
+                "\nMigration readiness ({}): {}\n",
+                migration_readiness.profile, migration_readiness.status
+            ));
+
+            for reason in &migration_readiness.reasons {
+                output.push_str(&format!("* {}\n", reason));
+            }
+        }
+
+        if let Some(top_blobs) = &self.top_blobs {
+            output.push_str("\nLargest blobs:\n");
+
+            for blob in top_blobs {
+                output.push_str(&format!("* {}\n", blob.to_text()));
+            }
+        }
+
+        if let Some(recommendations) = &self.recommendations {
+            output.push_str(&format!(
+                "\nRecommendations:\n{}",
+                recommendations.to_text()
+            ));
+        }
+
+        if let Some(breakdown) = &self.breakdown {
+            output.push_str(&format!(
+                "\nHistory size by path:\n{}",
+                breakdown::to_text(breakdown)
+            ));
+        }
+
+        if let Some(growth) = &self.growth {
+            output.push_str(&format!(
+                "\nGrowth by {}:\n{}",
+                growth.interval,
+                growth.to_text()
+            ));
+        }
+
+        if let Some(additional_refs) = &self.additional_refs {
+            output.push_str(&format!("\nSize by refs:\n{}", additional_refs.to_text()));
+        }
+
+        if let Some(lfs) = &self.lfs {
+            output.push_str(&format!("\nGit LFS objects:\n{}", lfs.to_text()));
+        }
+
+        if let Some(wiki) = &self.wiki {
+            output.push_str(&format!("\nWiki:\n{}", wiki.to_text()));
+        }
+
+        output
+    }
+}
+
+/// Renders the reports for several repositories together. In text output, each report is
+/// headed by the repository's name. In JSON output, the reports are rendered as an array, with
+/// a `repository` field added to each.
+pub fn render_all(reports: &[(String, Report)], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => reports
+            .iter()
+            .map(|(repository, report)| format!("{}:\n\n{}", repository, report.to_text()))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        OutputFormat::Json => to_json_string(
+            &reports
+                .iter()
+                .map(|(repository, report)| NamedReport { repository, report })
+                .collect::<Vec<NamedReport>>(),
+        ),
+    }
+}
+
+#[test]
+fn to_text_lists_policy_violations() {
+    let report = Report {
+        policy_violations: Some(vec![Violation {
+            metric: "reference_count".to_string(),
+            value: 12_500,
+            limit: 10_000,
+            referent: None,
+        }]),
+        ..Default::default()
+    };
+
+    assert!(report.to_text().ends_with(
+        "\nPolicy violations:\n* reference_count is 12.5 k, which exceeds the limit of 10.0 k\n"
+    ));
+}
+
+#[test]
+fn check_checks_the_wiki_against_the_policy_too() {
+    let policy = Policy::from_toml("[limits]\nmax_blob_size = 10\n").unwrap();
+    let report = Report {
+        wiki: Some(Wiki {
+            repository: "github/gh-sizer.wiki".to_string(),
+            report: Some(Box::new(Report::new(SizerReport {
+                max_blob_size: 13,
+                ..Default::default()
+            }))),
+            no_commits_since: None,
+        }),
+        ..Default::default()
+    }
+    .check(Some(&policy), None);
+
+    assert_eq!(report.policy_violations, Some(vec![]));
+    assert_eq!(report.violation_count(), 1);
+}
+
+#[test]
+fn to_text_notes_when_no_policy_limits_were_exceeded() {
+    let report = Report {
+        policy_violations: Some(vec![]),
+        ..Default::default()
+    };
+
+    assert!(report
+        .to_text()
+        .ends_with("\nNo policy limits were exceeded\n"));
+}
+
+#[test]
+fn to_json_includes_policy_violations_alongside_git_sizer_fields() {
+    let report = Report {
+        sizer_report: SizerReport {
+            max_blob_size: 13,
+            ..Default::default()
+        },
+        policy_violations: Some(vec![Violation {
+            metric: "max_blob_size".to_string(),
+            value: 13,
+            limit: 10,
+            referent: None,
+        }]),
+        ..Default::default()
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&report.render(OutputFormat::Json)).unwrap();
+
+    assert_eq!(json["max_blob_size"], 13);
+    assert_eq!(json["policy_violations"][0]["limit"], 10);
+}
+
+#[test]
+fn to_text_includes_migration_readiness() {
+    let report = Report {
+        migration_readiness: Some(MigrationReadiness {
+            profile: "gei".to_string(),
+            status: Status::AtRisk,
+            reasons: vec![
+                "The largest file is 350 MiB, which is close to the limit of 400 MiB".to_string(),
+            ],
+        }),
+        ..Default::default()
+    };
+
+    assert!(report.to_text().ends_with(
+        "\nMigration readiness (gei): at risk\n* The largest file is 350 MiB, which is close to the limit of 400 MiB\n"
+    ));
+}
+
+#[test]
+fn to_text_lists_top_blobs() {
+    let report = Report {
+        top_blobs: Some(vec![LargeBlob {
+            oid: "5dd01c177f5d7d1be5346a5bc18a569a7410c2ef".to_string(),
+            size: 150 * 1024 * 1024,
+            paths: vec!["assets/intro.mp4".to_string()],
+            commit: "8b8c9f6ac4f0dcb7b2b0fbd7b1c6a1b0b1e0c2f3".to_string(),
+            date: "2023-06-01T09:30:00+01:00".to_string(),
+        }]),
+        ..Default::default()
+    };
+
+    assert!(report.to_text().ends_with(
+        "\nLargest blobs:\n* assets/intro.mp4: 150 MiB, introduced in 8b8c9f6 on 2023-06-01\n"
+    ));
+}
+
+#[test]
+fn parse_reads_both_output_formats() {
+    let report = Report {
+        sizer_report: SizerReport {
+            unique_commit_count: 42,
+            ..Default::default()
+        },
+        migration_readiness: Some(MigrationReadiness {
+            profile: "gei".to_string(),
+            status: Status::Migratable,
+            reasons: vec![],
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        Report::parse(&report.render(OutputFormat::Json)).unwrap(),
+        report
+    );
+    assert_eq!(
+        Report::parse(&report.render(OutputFormat::Text))
+            .unwrap()
+            .sizer_report
+            .unique_commit_count,
+        42
+    );
+    assert!(Report::parse("\n").is_err());
+}
+
+#[test]
+fn parse_rejects_json_which_isnt_a_git_sizer_report() {
+    let estimate = crate::estimate::Estimate::from_disk_usage("github/gh-sizer", 1024);
+
+    for contents in ["{}", &estimate.render(OutputFormat::Json)] {
+        assert_eq!(
+            Report::parse(contents).unwrap_err().to_string(),
+            "The file isn't a git-sizer report, as it doesn't include `unique_commit_count`"
+        );
+    }
+}
+
+#[test]
+fn to_json_omits_policy_violations_without_a_policy() {
+    let sizer_report = SizerReport::default();
+
+    assert_eq!(
+        Report::new(sizer_report.clone()).render(OutputFormat::Json),
+        sizer_report.to_json()
+    );
+}
+
+#[test]
+fn render_all_identifies_each_repository() {
+    let reports = vec![
+        ("github/first-repo".to_string(), Report::default()),
+        ("octocat/second-repo".to_string(), Report::default()),
+    ];
+
+    let text = render_all(&reports, OutputFormat::Text);
+    assert!(text.starts_with("github/first-repo:\n\n| Name "));
+    assert!(text.contains("\noctocat/second-repo:\n\n| Name "));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&render_all(&reports, OutputFormat::Json)).unwrap();
+    assert_eq!(json[0]["repository"], "github/first-repo");
+    assert_eq!(json[1]["repository"], "octocat/second-repo");
+    assert_eq!(json[1]["unique_commit_count"], 0);
+}
@@ -0,0 +1,431 @@
+use std::cmp::Ordering;
+use std::io::{Error, Write};
+
+use crate::enums::{SortBy, Visibility};
+use crate::github_repository_lister::Repository;
+use crate::name_pattern::NamePattern;
+
+/// Criteria for choosing which of an owner's repositories to size, based on their names and
+/// the metadata returned by `gh repo list`
+#[derive(Clone, Debug, Default)]
+pub struct RepositoryFilter {
+    pub exclude_forks: bool,
+    pub exclude_archived: bool,
+    pub visibility: Option<Visibility>,
+    /// Only include repositories with this primary language, compared case-insensitively
+    pub language: Option<String>,
+    /// Only include repositories with all of these topics
+    pub topics: Vec<String>,
+    /// Only include repositories pushed to on or after this date, in `YYYY-MM-DD` format
+    pub pushed_since: Option<String>,
+    /// Only include repositories whose disk usage, as reported by the GitHub API, is at least
+    /// this many bytes
+    pub min_size: Option<u64>,
+    /// Only include repositories whose disk usage, as reported by the GitHub API, is at most
+    /// this many bytes
+    pub max_size: Option<u64>,
+    /// If any are set, only include repositories whose names match at least one of these
+    pub include: Vec<NamePattern>,
+    /// Exclude repositories whose names match any of these
+    pub exclude: Vec<NamePattern>,
+}
+
+impl RepositoryFilter {
+    /// Whether any criteria have been set, or the filter includes every repository
+    pub fn is_active(&self) -> bool {
+        self.exclude_forks
+            || self.exclude_archived
+            || self.visibility.is_some()
+            || self.language.is_some()
+            || !self.topics.is_empty()
+            || self.pushed_since.is_some()
+            || self.min_size.is_some()
+            || self.max_size.is_some()
+            || !self.include.is_empty()
+            || !self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, repository: &Repository) -> bool {
+        if self.exclude_forks && repository.is_fork {
+            return false;
+        }
+
+        if self.exclude_archived && repository.is_archived {
+            return false;
+        }
+
+        if let Some(visibility) = self.visibility {
+            if !repository
+                .visibility
+                .eq_ignore_ascii_case(&visibility.to_string())
+            {
+                return false;
+            }
+        }
+
+        if let Some(language) = &self.language {
+            if !repository
+                .primary_language
+                .as_ref()
+                .is_some_and(|primary_language| primary_language.eq_ignore_ascii_case(language))
+            {
+                return false;
+            }
+        }
+
+        if !self.topics.iter().all(|topic| {
+            repository
+                .repository_topics
+                .iter()
+                .any(|repository_topic| repository_topic.eq_ignore_ascii_case(topic))
+        }) {
+            return false;
+        }
+
+        if let Some(pushed_since) = &self.pushed_since {
+            // ISO 8601 timestamps sort in the same order as the times they represent, so a
+            // timestamp on or after the date compares greater than or equal to it
+            if repository
+                .pushed_at
+                .as_ref()
+                .is_none_or(|pushed_at| pushed_at.as_str() < pushed_since.as_str())
+            {
+                return false;
+            }
+        }
+
+        if self.min_size.is_some() || self.max_size.is_some() {
+            // Repositories whose size isn't known can't be shown to be inside the window
+            let Some(size) = repository.size() else {
+                return false;
+            };
+
+            if self.min_size.is_some_and(|min_size| size < min_size)
+                || self.max_size.is_some_and(|max_size| size > max_size)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Applies the filter to a list of repositories, reporting how many repositories each
+    /// include pattern matched and each exclude pattern removed
+    pub fn apply(
+        &self,
+        repositories: Vec<Repository>,
+        stderr: &mut impl Write,
+    ) -> Result<Vec<Repository>, Error> {
+        let mut repositories: Vec<Repository> = repositories
+            .into_iter()
+            .filter(|repository| self.matches(repository))
+            .collect();
+
+        if !self.include.is_empty() {
+            let mut included = vec![false; repositories.len()];
+
+            for pattern in &self.include {
+                let mut matched_count = 0;
+
+                for (index, repository) in repositories.iter().enumerate() {
+                    if pattern.is_match(&repository.name) {
+                        included[index] = true;
+                        matched_count += 1;
+                    }
+                }
+
+                writeln!(
+                    stderr,
+                    "Include pattern `{}` matched {}",
+                    pattern,
+                    count_repositories(matched_count)
+                )?;
+            }
+
+            let mut included = included.into_iter();
+            repositories.retain(|_| included.next().unwrap());
+        }
+
+        for pattern in &self.exclude {
+            let count_before = repositories.len();
+            repositories.retain(|repository| !pattern.is_match(&repository.name));
+
+            writeln!(
+                stderr,
+                "Exclude pattern `{}` removed {}",
+                pattern,
+                count_repositories(count_before - repositories.len())
+            )?;
+        }
+
+        Ok(repositories)
+    }
+}
+
+/// Describes a number of repositories, e.g. `1 repository` or `2 repositories`
+fn count_repositories(count: usize) -> String {
+    if count == 1 {
+        "1 repository".to_string()
+    } else {
+        format!("{} repositories", count)
+    }
+}
+
+/// Sorts repositories using their names or the metadata returned by `gh repo list`. Sizes and
+/// push dates are sorted largest or most recent first, unless `reverse` is set. Repositories
+/// without the metadata being sorted on, e.g. from a repos file, are left at the end in their
+/// original order.
+pub fn sort(repositories: &mut [Repository], sort_by: SortBy, reverse: bool) {
+    let has_value = |repository: &Repository| match sort_by {
+        SortBy::Size => repository.disk_usage.is_some(),
+        SortBy::Name => true,
+        SortBy::Pushed => repository.pushed_at.is_some(),
+    };
+
+    repositories.sort_by(|a, b| {
+        let ordering = match sort_by {
+            SortBy::Size => compare_known_values(b.size(), a.size()),
+            SortBy::Name => Some(a.nwo().to_lowercase().cmp(&b.nwo().to_lowercase())),
+            SortBy::Pushed => compare_known_values(b.pushed_at.as_ref(), a.pushed_at.as_ref()),
+        };
+
+        match ordering {
+            Some(ordering) if reverse => ordering.reverse(),
+            Some(ordering) => ordering,
+            // Put the repositories with a value first, whichever direction they are sorted in
+            None => has_value(b).cmp(&has_value(a)),
+        }
+    });
+}
+
+/// Compares two values if both are known
+fn compare_known_values<T: Ord>(a: Option<T>, b: Option<T>) -> Option<Ordering> {
+    Some(a?.cmp(&b?))
+}
+
+/// Parses a date in `YYYY-MM-DD` format, for use as a command line argument
+pub fn parse_date(date: &str) -> Result<String, String> {
+    let is_valid = regex::Regex::new(r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])$")
+        .unwrap()
+        .is_match(date);
+
+    if is_valid {
+        Ok(date.to_string())
+    } else {
+        Err(format!("`{}` isn't a date in YYYY-MM-DD format", date))
+    }
+}
+
+#[cfg(test)]
+fn example_repositories() -> Vec<Repository> {
+    vec![
+        Repository {
+            name: "active".to_string(),
+            visibility: "PUBLIC".to_string(),
+            primary_language: Some("Rust".to_string()),
+            repository_topics: vec!["cli".to_string(), "git".to_string()],
+            pushed_at: Some("2024-03-01T12:00:00Z".to_string()),
+            ..Default::default()
+        },
+        Repository {
+            name: "fork".to_string(),
+            is_fork: true,
+            visibility: "PUBLIC".to_string(),
+            primary_language: Some("Go".to_string()),
+            pushed_at: Some("2024-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        },
+        Repository {
+            name: "archived".to_string(),
+            is_archived: true,
+            visibility: "PRIVATE".to_string(),
+            repository_topics: vec!["cli".to_string()],
+            pushed_at: Some("2019-06-30T09:00:00Z".to_string()),
+            ..Default::default()
+        },
+    ]
+}
+
+#[cfg(test)]
+fn names(repositories: Result<Vec<Repository>, Error>) -> Vec<String> {
+    repositories
+        .unwrap()
+        .into_iter()
+        .map(|repository| repository.name)
+        .collect()
+}
+
+#[test]
+fn default_filter_includes_every_repository() {
+    let filter = RepositoryFilter::default();
+
+    assert!(!filter.is_active());
+    assert_eq!(
+        names(filter.apply(example_repositories(), &mut Vec::new())),
+        vec!["active", "fork", "archived"]
+    );
+}
+
+#[test]
+fn filter_excludes_forks_and_archived_repositories() {
+    let filter = RepositoryFilter {
+        exclude_forks: true,
+        exclude_archived: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        names(filter.apply(example_repositories(), &mut Vec::new())),
+        vec!["active"]
+    );
+}
+
+#[test]
+fn filter_matches_visibility_language_and_topics() {
+    let by_visibility = RepositoryFilter {
+        visibility: Some(Visibility::Private),
+        ..Default::default()
+    };
+    assert_eq!(
+        names(by_visibility.apply(example_repositories(), &mut Vec::new())),
+        vec!["archived"]
+    );
+
+    let by_language = RepositoryFilter {
+        language: Some("go".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        names(by_language.apply(example_repositories(), &mut Vec::new())),
+        vec!["fork"]
+    );
+
+    let by_topics = RepositoryFilter {
+        topics: vec!["cli".to_string(), "git".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        names(by_topics.apply(example_repositories(), &mut Vec::new())),
+        vec!["active"]
+    );
+}
+
+#[test]
+fn filter_matches_repositories_pushed_on_or_after_date() {
+    let filter = RepositoryFilter {
+        pushed_since: Some("2024-01-01".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        names(filter.apply(example_repositories(), &mut Vec::new())),
+        vec!["active", "fork"]
+    );
+}
+
+#[test]
+fn filter_reports_repositories_matched_by_include_and_exclude_patterns() {
+    let filter = RepositoryFilter {
+        include: vec![
+            NamePattern::parse("a*").unwrap(),
+            NamePattern::parse("/^f/").unwrap(),
+        ],
+        exclude: vec![NamePattern::parse("*ive").unwrap()],
+        ..Default::default()
+    };
+    let mut stderr = Vec::new();
+
+    assert_eq!(
+        names(filter.apply(example_repositories(), &mut stderr)),
+        vec!["fork", "archived"]
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&stderr),
+        "Include pattern `a*` matched 2 repositories\n\
+         Include pattern `/^f/` matched 1 repository\n\
+         Exclude pattern `*ive` removed 1 repository\n"
+    );
+}
+
+#[test]
+fn filter_matches_repositories_within_size_window() {
+    let repositories = vec![
+        Repository {
+            name: "small".to_string(),
+            disk_usage: Some(10),
+            ..Default::default()
+        },
+        Repository {
+            name: "medium".to_string(),
+            disk_usage: Some(1000),
+            ..Default::default()
+        },
+        Repository {
+            name: "unknown".to_string(),
+            ..Default::default()
+        },
+    ];
+    let filter = RepositoryFilter {
+        min_size: Some(100 * 1024),
+        ..Default::default()
+    };
+
+    assert!(filter.is_active());
+    assert_eq!(
+        names(filter.apply(repositories.clone(), &mut Vec::new())),
+        vec!["medium"]
+    );
+
+    let filter = RepositoryFilter {
+        max_size: Some(100 * 1024),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        names(filter.apply(repositories, &mut Vec::new())),
+        vec!["small"]
+    );
+}
+
+#[test]
+fn sort_orders_by_metadata_leaving_unknown_values_last() {
+    let mut repositories = example_repositories();
+    repositories.push(Repository {
+        name: "Beta".to_string(),
+        ..Default::default()
+    });
+    repositories[0].disk_usage = Some(10);
+    repositories[2].disk_usage = Some(1000);
+
+    sort(&mut repositories, SortBy::Size, false);
+    assert_eq!(
+        names(Ok(repositories.clone())),
+        vec!["archived", "active", "fork", "Beta"]
+    );
+
+    sort(&mut repositories, SortBy::Size, true);
+    assert_eq!(
+        names(Ok(repositories.clone())),
+        vec!["active", "archived", "fork", "Beta"]
+    );
+
+    sort(&mut repositories, SortBy::Pushed, false);
+    assert_eq!(
+        names(Ok(repositories.clone())),
+        vec!["active", "fork", "archived", "Beta"]
+    );
+
+    sort(&mut repositories, SortBy::Name, false);
+    assert_eq!(
+        names(Ok(repositories)),
+        vec!["active", "archived", "Beta", "fork"]
+    );
+}
+
+#[test]
+fn parse_date_accepts_only_iso_8601_dates() {
+    assert_eq!(parse_date("2024-01-31"), Ok("2024-01-31".to_string()));
+    assert!(parse_date("2024-13-01").is_err());
+    assert!(parse_date("31/01/2024").is_err());
+}
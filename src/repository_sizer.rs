@@ -0,0 +1,351 @@
+use std::io::Error;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+use crate::additional_refs::{self, AdditionalRefs};
+use crate::breakdown::breakdown;
+use crate::clone_cache::{CloneCache, DEFAULT_HOSTNAME};
+use crate::enums::Interval;
+use crate::gh;
+use crate::growth::growth;
+use crate::history::History;
+use crate::lfs::lfs_objects;
+use crate::recommendations::{recommend, LARGE_BLOB_SIZE};
+use crate::ref_selection::RefSelection;
+use crate::report::Report;
+use crate::sizer_report::SizerReport;
+use crate::top_blobs::largest_blobs;
+use crate::wiki;
+
+#[cfg(test)]
+use mockall::{automock, predicate::*};
+
+#[cfg_attr(test, automock)]
+pub trait RepositorySizer {
+    fn call(&self, nwo: &str) -> Result<Report, Error>;
+}
+
+/// Analysis to run on a repository's history in addition to `git-sizer`
+#[derive(Clone, Debug, Default)]
+pub struct AnalysisOptions {
+    /// If set, report this many of the largest blobs in the repository's history
+    pub top_blobs: Option<usize>,
+    /// Whether to recommend how to make the repository smaller by moving large files to Git
+    /// LFS or removing them from history
+    pub recommend: bool,
+    /// If set, break down the size of the repository's history by path, down to this many
+    /// levels of directories
+    pub breakdown_depth: Option<usize>,
+    /// If set, report how the repository has grown over time, by month or year
+    pub growth: Option<Interval>,
+    /// Whether to report the Git LFS objects referenced by pointer files in the repository's
+    /// history, which `git-sizer` doesn't count
+    pub include_lfs: bool,
+}
+
+impl AnalysisOptions {
+    fn needs_history(&self) -> bool {
+        self.top_blobs.is_some()
+            || self.recommend
+            || self.breakdown_depth.is_some()
+            || self.growth.is_some()
+            || self.include_lfs
+    }
+}
+
+#[derive(Default)]
+pub struct RepositorySizerImpl {
+    /// The GitHub host to clone repositories from, or `None` for the default host
+    pub hostname: Option<String>,
+    /// If set, bare clones are kept in this cache between runs rather than thrown away
+    pub cache: Option<CloneCache>,
+    /// Which refs and how much history to size. Selecting history by date makes a shallow
+    /// clone, so it can't be combined with a cache.
+    pub ref_selection: RefSelection,
+    /// Extra arguments to pass to `git-sizer`, which must have been checked with
+    /// `parse_sizer_argument`
+    pub sizer_arguments: Vec<String>,
+    pub analysis_options: AnalysisOptions,
+    /// Refspecs for refs to fetch into the clone in addition to its branches and tags, e.g.
+    /// pull request refs, whose size is reported separately. These can't be fetched into a
+    /// cached clone, as they'd be kept for later runs.
+    pub fetch_refspecs: Vec<String>,
+    /// Whether to also size each repository's wiki, if it has one
+    pub include_wiki: bool,
+}
+
+impl RepositorySizer for RepositorySizerImpl {
+    fn call(&self, nwo: &str) -> Result<Report, Error> {
+        let mut report = self.size(nwo, &self.fetch_refspecs)?;
+
+        // Wikis don't have pull requests or other refs beyond their branches, so only their
+        // default refs are fetched
+        if self.include_wiki && !wiki::is_wiki(nwo) {
+            report.wiki = Some(wiki::size_wiki(
+                nwo,
+                self.ref_selection.since.as_deref(),
+                |wiki_nwo| self.size(wiki_nwo, &[]),
+            )?);
+        }
+
+        Ok(report)
+    }
+}
+
+impl RepositorySizerImpl {
+    /// Clones and sizes a single repository, fetching extra refs with the given refspecs
+    fn size(&self, nwo: &str, fetch_refspecs: &[String]) -> Result<Report, Error> {
+        if let Some(cache) = &self.cache {
+            let hostname = self.hostname.as_deref().unwrap_or(DEFAULT_HOSTNAME);
+            let path = cache.fetch(hostname, nwo, |path| self.clone_bare(nwo, path))?;
+
+            eprintln!("Running git-sizer on cached repository...");
+
+            return self.analyze(&path, fetch_refspecs);
+        }
+
+        let temporary_directory = tempdir()?;
+
+        self.clone_bare(nwo, temporary_directory.path())?;
+
+        if !fetch_refspecs.is_empty() {
+            additional_refs::fetch(
+                temporary_directory.path(),
+                fetch_refspecs,
+                self.ref_selection.since.as_deref(),
+            )?;
+        }
+
+        eprintln!("Running git-sizer on cloned repository...");
+
+        self.analyze(temporary_directory.path(), fetch_refspecs)
+    }
+
+    fn analyze(&self, path: &Path, fetch_refspecs: &[String]) -> Result<Report, Error> {
+        let mut report = analyze_repository_at(
+            path,
+            &additional_refs::with_additional_refs(&self.ref_selection, fetch_refspecs),
+            &self.sizer_arguments,
+            &self.analysis_options,
+        )?;
+
+        if !fetch_refspecs.is_empty() {
+            eprintln!("Running git-sizer without the additional refs...");
+
+            let mut sizer_arguments = self.ref_selection.sizer_arguments();
+            sizer_arguments.extend_from_slice(&self.sizer_arguments);
+            sizer_arguments.extend(additional_refs::exclude_arguments(fetch_refspecs));
+
+            report.additional_refs = Some(AdditionalRefs::compare(
+                fetch_refspecs,
+                additional_refs::count_refs(path, fetch_refspecs)?,
+                &report.sizer_report,
+                &size_repository_at(path, &sizer_arguments)?,
+            ));
+        }
+
+        Ok(report)
+    }
+
+    /// Makes a bare clone of a GitHub repository into a directory
+    fn clone_bare(&self, nwo: &str, path: &Path) -> Result<(), Error> {
+        eprintln!(
+            "Cloning {} from {}...",
+            nwo,
+            self.hostname.as_deref().unwrap_or("GitHub")
+        );
+
+        let mut clone_command = gh::command("gh", self.hostname.as_deref());
+        clone_command
+            .arg("repo")
+            .arg("clone")
+            .arg(nwo)
+            .arg(path)
+            .arg("--")
+            .arg("--bare");
+
+        if let Some(since) = &self.ref_selection.since {
+            clone_command.arg(format!("--shallow-since={}", since));
+        }
+
+        let clone_output = clone_command.output()?;
+
+        if !clone_output.status.success() {
+            return Err(Error::other(
+                String::from_utf8_lossy(&clone_output.stderr).to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs `git-sizer` on a repository which is already on disk, either bare or with a working
+/// copy, passing any extra arguments, e.g. to choose which refs to size
+pub fn size_repository_at(path: &Path, sizer_arguments: &[String]) -> Result<SizerReport, Error> {
+    let mut sizer_command = Command::new("git-sizer");
+
+    sizer_command.current_dir(path);
+    sizer_command.arg("--verbose");
+    sizer_command.arg("--json");
+    sizer_command.args(sizer_arguments);
+
+    let sizer_command_output = sizer_command.output()?;
+
+    if !sizer_command_output.status.success() {
+        return Err(Error::other(
+            String::from_utf8_lossy(&sizer_command_output.stderr).to_string(),
+        ));
+    }
+
+    let output_text = String::from_utf8_lossy(&sizer_command_output.stdout);
+    SizerReport::from_json(&output_text)
+}
+
+/// Runs `git-sizer` on a repository which is already on disk, along with any additional analysis
+/// of its history, only looking at the selected refs
+pub fn analyze_repository_at(
+    path: &Path,
+    ref_selection: &RefSelection,
+    sizer_arguments: &[String],
+    analysis_options: &AnalysisOptions,
+) -> Result<Report, Error> {
+    let mut all_sizer_arguments = ref_selection.sizer_arguments();
+    all_sizer_arguments.extend_from_slice(sizer_arguments);
+
+    let mut report = Report::new(size_repository_at(path, &all_sizer_arguments)?);
+
+    if !ref_selection.is_empty() {
+        report.selection = Some(ref_selection.clone());
+    }
+
+    if analysis_options.needs_history() {
+        eprintln!("Analyzing the repository's history...");
+
+        let history = History::read(path, ref_selection)?;
+
+        report.top_blobs = analysis_options
+            .top_blobs
+            .map(|count| largest_blobs(&history, count));
+        report.recommendations = analysis_options
+            .recommend
+            .then(|| recommend(&history, LARGE_BLOB_SIZE));
+        report.breakdown = analysis_options
+            .breakdown_depth
+            .map(|depth| breakdown(&history, depth));
+        report.growth = analysis_options
+            .growth
+            .map(|interval| growth(&history, interval));
+
+        if analysis_options.include_lfs {
+            report.lfs = Some(lfs_objects(path, &history)?);
+        }
+    }
+
+    Ok(report)
+}
+
+/// `git-sizer` options which can't be passed through, because gh-sizer needs `git-sizer`'s
+/// verbose JSON output in the format it knows how to parse
+const DENIED_SIZER_OPTIONS: &[&str] = &[
+    "--json",
+    "--json-version",
+    "--verbose",
+    "--no-verbose",
+    "--help",
+    "--version",
+];
+
+/// Short forms of the denied `git-sizer` options, which can be combined, e.g. `-vj`
+const DENIED_SIZER_SHORT_OPTIONS: &[char] = &['j', 'v', 'h'];
+
+/// `git-sizer` options which choose which metrics to show. gh-sizer always runs `git-sizer`
+/// with `--verbose`, and renders the table itself from every metric in the JSON output, so
+/// these would have no effect.
+const THRESHOLD_SIZER_OPTIONS: &[&str] = &["--threshold", "--critical"];
+
+/// Checks an argument to pass through to `git-sizer`, for use as a command line argument
+pub fn parse_sizer_argument(argument: &str) -> Result<String, String> {
+    let option_name = argument.split('=').next().unwrap_or(argument);
+
+    if THRESHOLD_SIZER_OPTIONS.contains(&option_name) {
+        return Err(format!(
+            "`{}` can't be passed to git-sizer, because gh-sizer always reports every metric",
+            argument
+        ));
+    }
+
+    let is_denied = if argument.starts_with("--") {
+        DENIED_SIZER_OPTIONS.contains(&option_name)
+    } else if let Some(short_options) = argument.strip_prefix('-') {
+        short_options
+            .chars()
+            .any(|character| DENIED_SIZER_SHORT_OPTIONS.contains(&character))
+    } else {
+        false
+    };
+
+    if is_denied {
+        Err(format!(
+            "`{}` can't be passed to git-sizer, because gh-sizer relies on git-sizer's output format",
+            argument
+        ))
+    } else {
+        Ok(argument.to_string())
+    }
+}
+
+/// Whether a path is a Git repository, either bare or with a working copy
+pub fn is_git_repository(path: &Path) -> bool {
+    path.join(".git").exists()
+        || (path.join("HEAD").is_file()
+            && path.join("objects").is_dir()
+            && path.join("refs").is_dir())
+}
+
+#[test]
+fn parse_sizer_argument_rejects_options_which_change_the_output_format() {
+    assert!(parse_sizer_argument("--names=full").is_ok());
+    assert!(parse_sizer_argument("full").is_ok());
+    assert!(parse_sizer_argument("--no-progress").is_ok());
+    assert_eq!(
+        parse_sizer_argument("--json-version=1"),
+        Err("`--json-version=1` can't be passed to git-sizer, because gh-sizer relies on git-sizer's output format".to_string())
+    );
+    assert!(parse_sizer_argument("--json").is_err());
+    assert!(parse_sizer_argument("-j").is_err());
+    assert!(parse_sizer_argument("-vj").is_err());
+}
+
+#[test]
+fn parse_sizer_argument_rejects_options_which_choose_which_metrics_to_show() {
+    assert_eq!(
+        parse_sizer_argument("--threshold=2"),
+        Err("`--threshold=2` can't be passed to git-sizer, because gh-sizer always reports every metric".to_string())
+    );
+    assert!(parse_sizer_argument("--threshold").is_err());
+    assert!(parse_sizer_argument("--critical").is_err());
+}
+
+#[test]
+fn is_git_repository_detects_bare_and_non_bare_repositories() {
+    let directory = tempfile::tempdir().unwrap();
+    let non_bare = directory.path().join("non-bare");
+    let bare = directory.path().join("bare.git");
+
+    for (path, extra_args) in [(&non_bare, vec![]), (&bare, vec!["--bare"])] {
+        let status = Command::new("git")
+            .arg("init")
+            .arg("--quiet")
+            .args(extra_args)
+            .arg(path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    assert!(is_git_repository(&non_bare));
+    assert!(is_git_repository(&bare));
+    assert!(!is_git_repository(directory.path()));
+}
@@ -0,0 +1,413 @@
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::clone_cache::DEFAULT_HOSTNAME;
+use crate::enterprise::EnterpriseOrganizationLister;
+use crate::github_repository_lister::{GitHubRepositoryLister, Repository};
+use crate::repository_filter::RepositoryFilter;
+
+#[cfg(test)]
+use crate::enterprise::MockEnterpriseOrganizationLister;
+#[cfg(test)]
+use crate::github_repository_lister::MockGitHubRepositoryLister;
+#[cfg(test)]
+use mockall::predicate::eq;
+
+/// Where the list of repositories to size comes from
+#[derive(Clone, Debug)]
+pub enum RepositorySource {
+    /// All of the repositories owned by one or more users or organizations
+    Owners(Vec<String>),
+    /// All of the repositories owned by the organizations in an enterprise
+    Enterprise {
+        slug: String,
+        organizations: Vec<String>,
+    },
+    /// The repositories listed in a file, one per line, or on stdin if the path is `-`. Any
+    /// repositories listed by URL must be on the given host, or github.com if it is `None`.
+    ReposFile {
+        path: String,
+        hostname: Option<String>,
+    },
+}
+
+impl fmt::Display for RepositorySource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepositorySource::Owners(owners) => match owners.split_last() {
+                Some((last, [])) => write!(f, "owned by {}", last),
+                Some((last, rest)) => write!(f, "owned by {} and {}", rest.join(", "), last),
+                None => write!(f, "owned by nobody"),
+            },
+            RepositorySource::Enterprise { slug, .. } => {
+                write!(f, "owned by organizations in the {} enterprise", slug)
+            }
+            RepositorySource::ReposFile { path, .. } if path == "-" => {
+                write!(f, "listed on stdin")
+            }
+            RepositorySource::ReposFile { path, .. } => write!(f, "listed in {}", path),
+        }
+    }
+}
+
+impl RepositorySource {
+    /// Builds a source for all of the repositories in an enterprise, listing its organizations
+    pub fn for_enterprise(
+        slug: &str,
+        enterprise_organization_lister: &impl EnterpriseOrganizationLister,
+        stderr: &mut impl Write,
+    ) -> Result<RepositorySource, Error> {
+        let organizations = enterprise_organization_lister.call(slug)?;
+
+        writeln!(
+            stderr,
+            "Found {} organizations in the {} enterprise",
+            organizations.len(),
+            slug
+        )?;
+
+        Ok(RepositorySource::Enterprise {
+            slug: slug.to_string(),
+            organizations,
+        })
+    }
+
+    /// Lists the repositories from the source which match a filter
+    pub fn list(
+        &self,
+        repository_filter: &RepositoryFilter,
+        github_repository_lister: &impl GitHubRepositoryLister,
+        stderr: &mut impl Write,
+    ) -> Result<Vec<Repository>, Error> {
+        let repositories = match self {
+            RepositorySource::Owners(owners)
+            | RepositorySource::Enterprise {
+                organizations: owners,
+                ..
+            } => {
+                let mut repositories = Vec::new();
+
+                for owner in owners {
+                    repositories.extend(github_repository_lister.call(owner)?);
+                }
+
+                repositories
+            }
+            RepositorySource::ReposFile { path, hostname } => {
+                read_repos_file(path, hostname.as_deref())?
+            }
+        };
+
+        let listed_count = repositories.len();
+        let repositories = repository_filter.apply(repositories, stderr)?;
+
+        if repository_filter.is_active() {
+            writeln!(
+                stderr,
+                "Excluded {} of {} repositories {} using filters",
+                listed_count - repositories.len(),
+                listed_count,
+                self
+            )?;
+        }
+
+        Ok(repositories)
+    }
+}
+
+/// Reads a list of repositories from a file, or from stdin if the path is `-`. Each line can
+/// either be an `owner/repo` or a repository URL. URLs must be on the host the repositories
+/// will be sized on, or github.com if `hostname` is `None`. Blank lines and lines starting with
+/// `#` are ignored.
+pub fn read_repos_file(path: &str, hostname: Option<&str>) -> Result<Vec<Repository>, Error> {
+    let contents = if path == "-" {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!("Failed to read repos file {}: {}", path, e),
+            )
+        })?
+    };
+
+    parse_repos_file(&contents, hostname.unwrap_or(DEFAULT_HOSTNAME)).map_err(|e| {
+        let description = if path == "-" {
+            "the repos listed on stdin".to_string()
+        } else {
+            format!("repos file {}", path)
+        };
+
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse {}: {}", description, e),
+        )
+    })
+}
+
+fn parse_repos_file(contents: &str, hostname: &str) -> Result<Vec<Repository>, String> {
+    let mut repositories: Vec<Repository> = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let repository = parse_repository(line).ok_or_else(|| {
+            format!(
+                "line {} isn't an `owner/repo` or a repository URL: {}",
+                index + 1,
+                line
+            )
+        })?;
+
+        // Sizing a repository by name on the wrong host could size a different repository
+        if let Some(host) = &repository.host {
+            if !host.eq_ignore_ascii_case(hostname) {
+                return Err(format!(
+                    "line {} is a repository on {}, but repos are being sized on {}. Use `--hostname {}` to size repos on that host, and list repos on other hosts in a separate file: {}",
+                    index + 1,
+                    host,
+                    hostname,
+                    host,
+                    line
+                ));
+            }
+        }
+
+        if !repositories
+            .iter()
+            .any(|listed| listed.nwo() == repository.nwo())
+        {
+            repositories.push(repository);
+        }
+    }
+
+    Ok(repositories)
+}
+
+/// Parses an `owner/repo`, or a repository URL like `https://github.com/owner/repo` or
+/// `git@github.com:owner/repo.git`, keeping the host from a URL
+fn parse_repository(reference: &str) -> Option<Repository> {
+    let (host, path) = if let Some((_, rest)) = reference.split_once("://") {
+        // Skip any user info or port around the host
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?.split(':').next()?;
+
+        (Some(host), path)
+    } else if let Some((user_and_host, path)) = reference.split_once(':') {
+        let (_, host) = user_and_host.split_once('@')?;
+
+        (Some(host), path)
+    } else {
+        (None, reference)
+    };
+
+    if host.is_some_and(str::is_empty) {
+        return None;
+    }
+
+    let mut segments = path.split('/');
+    let owner = segments.next()?;
+    let name = segments.next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+
+    // An `owner/repo` must be exactly that, but URLs can point deeper into a repository, e.g.
+    // `https://github.com/owner/repo/tree/main`
+    if path == reference && segments.next().is_some() {
+        return None;
+    }
+
+    let is_valid_segment = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character))
+    };
+
+    if !is_valid_segment(owner) || !is_valid_segment(name) {
+        return None;
+    }
+
+    Some(Repository {
+        host: host.map(str::to_string),
+        ..Repository::new(owner, name)
+    })
+}
+
+#[test]
+fn parse_repos_file_accepts_nwos_and_urls_across_owners() {
+    let repositories = parse_repos_file(
+        "# Wave 1\n\
+         github/gh-sizer\n\
+         \n\
+         https://github.com/timrogers/litra-rs\n\
+         https://token@GitHub.com:443/platform/api.git/\n\
+         https://github.com/octocat/hello-world/tree/main\n\
+         git@github.com:octocat/Spoon-Knife.git\n\
+         github/gh-sizer\n",
+        "github.com",
+    )
+    .unwrap();
+
+    assert_eq!(
+        repositories
+            .iter()
+            .map(Repository::nwo)
+            .collect::<Vec<String>>(),
+        vec![
+            "github/gh-sizer",
+            "timrogers/litra-rs",
+            "platform/api",
+            "octocat/hello-world",
+            "octocat/Spoon-Knife"
+        ]
+    );
+    assert_eq!(repositories[0].host, None);
+    assert_eq!(repositories[2].host, Some("GitHub.com".to_string()));
+}
+
+#[test]
+fn parse_repos_file_rejects_urls_on_a_different_host() {
+    assert_eq!(
+        parse_repos_file(
+            "github/gh-sizer\nhttps://github.acme.com/platform/api.git\n",
+            "github.com"
+        )
+        .unwrap_err(),
+        "line 2 is a repository on github.acme.com, but repos are being sized on github.com. Use `--hostname github.acme.com` to size repos on that host, and list repos on other hosts in a separate file: https://github.acme.com/platform/api.git"
+    );
+    assert!(parse_repos_file("git@github.acme.com:platform/api.git\n", "github.com").is_err());
+    assert_eq!(
+        parse_repos_file(
+            "https://github.acme.com/platform/api.git\ngit@github.acme.com:platform/web.git\n",
+            "github.acme.com"
+        )
+        .unwrap()
+        .len(),
+        2
+    );
+}
+
+#[test]
+fn parse_repos_file_rejects_lines_which_arent_repositories() {
+    assert_eq!(
+        parse_repos_file("github/gh-sizer\njust-a-name\n", "github.com").unwrap_err(),
+        "line 2 isn't an `owner/repo` or a repository URL: just-a-name"
+    );
+    assert!(parse_repos_file("github/gh-sizer/extra\n", "github.com").is_err());
+    assert!(parse_repos_file("https://github.com/github\n", "github.com").is_err());
+}
+
+#[test]
+fn read_repos_file_errors_when_file_is_missing() {
+    assert!(read_repos_file("does-not-exist.txt", None)
+        .unwrap_err()
+        .to_string()
+        .starts_with("Failed to read repos file does-not-exist.txt"));
+}
+
+#[test]
+fn list_applies_filter_and_reports_exclusions() {
+    let mut lister_mock = MockGitHubRepositoryLister::new();
+
+    lister_mock.expect_call().returning(|owner| {
+        Ok(vec![
+            Repository::new(owner, "gh-sizer"),
+            Repository {
+                is_archived: true,
+                ..Repository::new(owner, "old-repo")
+            },
+        ])
+    });
+
+    let mut stderr = Vec::new();
+
+    let repositories = RepositorySource::Owners(vec!["github".to_string()])
+        .list(
+            &RepositoryFilter {
+                exclude_archived: true,
+                ..Default::default()
+            },
+            &lister_mock,
+            &mut stderr,
+        )
+        .unwrap();
+
+    assert_eq!(repositories, vec![Repository::new("github", "gh-sizer")]);
+    assert_eq!(
+        String::from_utf8_lossy(&stderr),
+        "Excluded 1 of 2 repositories owned by github using filters\n"
+    );
+}
+
+#[test]
+fn display_lists_owners() {
+    assert_eq!(
+        RepositorySource::Owners(vec!["org-a".to_string()]).to_string(),
+        "owned by org-a"
+    );
+    assert_eq!(
+        RepositorySource::Owners(vec![
+            "org-a".to_string(),
+            "org-b".to_string(),
+            "org-c".to_string()
+        ])
+        .to_string(),
+        "owned by org-a, org-b and org-c"
+    );
+    assert_eq!(
+        RepositorySource::ReposFile {
+            path: "-".to_string(),
+            hostname: None,
+        }
+        .to_string(),
+        "listed on stdin"
+    );
+}
+
+#[test]
+fn enterprise_source_lists_repositories_across_organizations() {
+    let mut organization_lister_mock = MockEnterpriseOrganizationLister::new();
+
+    organization_lister_mock
+        .expect_call()
+        .with(eq("acme"))
+        .returning(|_| Ok(vec!["org-a".to_string(), "org-b".to_string()]));
+
+    let mut lister_mock = MockGitHubRepositoryLister::new();
+
+    lister_mock
+        .expect_call()
+        .returning(|owner| Ok(vec![Repository::new(owner, "api")]))
+        .times(2);
+
+    let mut stderr = Vec::new();
+
+    let repository_source =
+        RepositorySource::for_enterprise("acme", &organization_lister_mock, &mut stderr).unwrap();
+    let repositories = repository_source
+        .list(&RepositoryFilter::default(), &lister_mock, &mut stderr)
+        .unwrap();
+
+    assert_eq!(
+        repositories,
+        vec![
+            Repository::new("org-a", "api"),
+            Repository::new("org-b", "api")
+        ]
+    );
+    assert_eq!(
+        repository_source.to_string(),
+        "owned by organizations in the acme enterprise"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&stderr),
+        "Found 2 organizations in the acme enterprise\n"
+    );
+}
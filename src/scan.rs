@@ -0,0 +1,200 @@
+use std::fs;
+use std::io::{Error, Write};
+use std::path::{Path, PathBuf};
+
+use crate::enums::OutputFormat;
+use crate::generate_script::{ensure_unique_output_paths, output_filename};
+use crate::migration_readiness::Profile;
+use crate::org::{size_all, target_outputs, Summary, Target};
+use crate::policy::Policy;
+use crate::report::Report;
+use crate::repository_sizer::{is_git_repository, size_repository_at};
+
+/// Finds all of the Git repositories, bare or with a working copy, in a directory tree. Once
+/// a repository is found, its contents aren't searched for further repositories.
+pub fn find_repositories(directory: &Path) -> Result<Vec<PathBuf>, Error> {
+    if is_git_repository(directory) {
+        return Ok(vec![directory.to_path_buf()]);
+    }
+
+    let mut repositories = Vec::new();
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(directory)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        if entry.file_type()?.is_dir() {
+            repositories.extend(find_repositories(&entry.path())?);
+        }
+    }
+
+    Ok(repositories)
+}
+
+/// The owner and repository name to use in output filenames for a repository found by a scan:
+/// the name of the directory containing it, and its own directory name without any `.git` suffix
+fn owner_and_repository_name(path: &Path) -> (String, String) {
+    let file_name = |path: Option<&Path>| {
+        path.and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    let repository_name = file_name(Some(path));
+    let repository_name = repository_name
+        .strip_suffix(".git")
+        .unwrap_or(&repository_name)
+        .to_string();
+
+    (file_name(path.parent()), repository_name)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn call(
+    directory: &str,
+    jobs: usize,
+    output_format: OutputFormat,
+    output_directory: &str,
+    output_filename_template: &str,
+    policy: Option<&Policy>,
+    profile: Option<&Profile>,
+    stderr: &mut (impl Write + Send),
+) -> Result<Summary, Error> {
+    let directory_path = fs::canonicalize(directory)?;
+    let repositories = find_repositories(&directory_path)?;
+
+    let targets: Vec<Target> = repositories
+        .iter()
+        .map(|path| {
+            let (owner, repository_name) = owner_and_repository_name(path);
+
+            Target {
+                name: path.display().to_string(),
+                output_path: PathBuf::from(output_directory).join(output_filename(
+                    output_filename_template,
+                    &owner,
+                    &repository_name,
+                )),
+            }
+        })
+        .collect();
+
+    ensure_unique_output_paths(&target_outputs(&targets))?;
+
+    writeln!(
+        stderr,
+        "Sizing {} repositories found in {} using {} jobs",
+        repositories.len(),
+        directory,
+        jobs
+    )?;
+
+    fs::create_dir_all(output_directory)?;
+
+    let summary = size_all(
+        &targets,
+        "repo",
+        jobs,
+        output_format,
+        &|target| {
+            Ok(Report::with_checks(
+                size_repository_at(Path::new(&target.name), &[])?,
+                policy,
+                profile,
+            ))
+        },
+        stderr,
+    );
+
+    summary.write_to(stderr, &format!("repositories found in {}", directory))?;
+
+    Ok(summary)
+}
+
+#[test]
+fn find_repositories_finds_bare_and_non_bare_repositories() {
+    let directory = tempfile::tempdir().unwrap();
+
+    for (path, bare) in [
+        ("mirrors/github/first-repo.git", true),
+        ("mirrors/github/second-repo.git", true),
+        ("checkouts/third-repo", false),
+        ("checkouts/third-repo/vendor/nested", false),
+    ] {
+        let mut command = std::process::Command::new("git");
+        command.arg("init").arg("--quiet");
+
+        if bare {
+            command.arg("--bare");
+        }
+
+        assert!(command
+            .arg(directory.path().join(path))
+            .status()
+            .unwrap()
+            .success());
+    }
+
+    fs::create_dir_all(directory.path().join("empty/directory")).unwrap();
+
+    let repositories: Vec<PathBuf> = find_repositories(directory.path())
+        .unwrap()
+        .into_iter()
+        .map(|path| path.strip_prefix(directory.path()).unwrap().to_path_buf())
+        .collect();
+
+    assert_eq!(
+        repositories,
+        vec![
+            PathBuf::from("checkouts/third-repo"),
+            PathBuf::from("mirrors/github/first-repo.git"),
+            PathBuf::from("mirrors/github/second-repo.git"),
+        ]
+    );
+}
+
+#[test]
+fn owner_and_repository_name_uses_parent_directory_and_strips_git_suffix() {
+    assert_eq!(
+        owner_and_repository_name(Path::new("/srv/mirrors/github/gh-sizer.git")),
+        ("github".to_string(), "gh-sizer".to_string())
+    );
+    assert_eq!(
+        owner_and_repository_name(Path::new("/home/me/gh-sizer")),
+        ("me".to_string(), "gh-sizer".to_string())
+    );
+}
+
+#[test]
+fn scan_refuses_to_save_same_named_repositories_to_one_file() {
+    let directory = tempfile::tempdir().unwrap();
+
+    for path in ["backups/a/repo.git", "backups/b/repo.git"] {
+        assert!(std::process::Command::new("git")
+            .arg("init")
+            .arg("--quiet")
+            .arg("--bare")
+            .arg(directory.path().join(path))
+            .status()
+            .unwrap()
+            .success());
+    }
+
+    let output_directory = directory.path().join("output");
+    let mut stderr = Vec::new();
+
+    let error = call(
+        directory.path().join("backups").to_str().unwrap(),
+        2,
+        OutputFormat::Text,
+        output_directory.to_str().unwrap(),
+        "${repository}.txt",
+        None,
+        None,
+        &mut stderr,
+    )
+    .unwrap_err();
+
+    assert!(error.to_string().contains("would both be saved to "));
+    assert!(!output_directory.exists());
+    assert!(stderr.is_empty());
+}
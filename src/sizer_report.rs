@@ -0,0 +1,847 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+
+use crate::enums::OutputFormat;
+
+/// A single measurement reported by `git-sizer`
+#[derive(
+    clap::ValueEnum,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    UniqueCommitCount,
+    UniqueCommitSize,
+    MaxCommitSize,
+    MaxHistoryDepth,
+    MaxParentCount,
+    UniqueTreeCount,
+    UniqueTreeSize,
+    UniqueTreeEntries,
+    MaxTreeEntries,
+    UniqueBlobCount,
+    UniqueBlobSize,
+    MaxBlobSize,
+    UniqueTagCount,
+    MaxTagDepth,
+    ReferenceCount,
+    MaxPathDepth,
+    MaxPathLength,
+    MaxExpandedTreeCount,
+    MaxExpandedBlobCount,
+    MaxExpandedBlobSize,
+    MaxExpandedLinkCount,
+    MaxExpandedSubmoduleCount,
+}
+
+/// Whether a metric is a plain count or a size in bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Count,
+    Bytes,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 22] = [
+        Metric::UniqueCommitCount,
+        Metric::UniqueCommitSize,
+        Metric::MaxCommitSize,
+        Metric::MaxHistoryDepth,
+        Metric::MaxParentCount,
+        Metric::UniqueTreeCount,
+        Metric::UniqueTreeSize,
+        Metric::UniqueTreeEntries,
+        Metric::MaxTreeEntries,
+        Metric::UniqueBlobCount,
+        Metric::UniqueBlobSize,
+        Metric::MaxBlobSize,
+        Metric::UniqueTagCount,
+        Metric::MaxTagDepth,
+        Metric::ReferenceCount,
+        Metric::MaxPathDepth,
+        Metric::MaxPathLength,
+        Metric::MaxExpandedTreeCount,
+        Metric::MaxExpandedBlobCount,
+        Metric::MaxExpandedBlobSize,
+        Metric::MaxExpandedLinkCount,
+        Metric::MaxExpandedSubmoduleCount,
+    ];
+
+    /// The key used for the metric in `git-sizer`'s JSON output
+    pub fn key(&self) -> &'static str {
+        match self {
+            Metric::UniqueCommitCount => "unique_commit_count",
+            Metric::UniqueCommitSize => "unique_commit_size",
+            Metric::MaxCommitSize => "max_commit_size",
+            Metric::MaxHistoryDepth => "max_history_depth",
+            Metric::MaxParentCount => "max_parent_count",
+            Metric::UniqueTreeCount => "unique_tree_count",
+            Metric::UniqueTreeSize => "unique_tree_size",
+            Metric::UniqueTreeEntries => "unique_tree_entries",
+            Metric::MaxTreeEntries => "max_tree_entries",
+            Metric::UniqueBlobCount => "unique_blob_count",
+            Metric::UniqueBlobSize => "unique_blob_size",
+            Metric::MaxBlobSize => "max_blob_size",
+            Metric::UniqueTagCount => "unique_tag_count",
+            Metric::MaxTagDepth => "max_tag_depth",
+            Metric::ReferenceCount => "reference_count",
+            Metric::MaxPathDepth => "max_path_depth",
+            Metric::MaxPathLength => "max_path_length",
+            Metric::MaxExpandedTreeCount => "max_expanded_tree_count",
+            Metric::MaxExpandedBlobCount => "max_expanded_blob_count",
+            Metric::MaxExpandedBlobSize => "max_expanded_blob_size",
+            Metric::MaxExpandedLinkCount => "max_expanded_link_count",
+            Metric::MaxExpandedSubmoduleCount => "max_expanded_submodule_count",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Metric> {
+        Metric::ALL.into_iter().find(|metric| metric.key() == key)
+    }
+
+    /// The name used for the metric in `git-sizer`'s text output
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::UniqueCommitCount
+            | Metric::UniqueTreeCount
+            | Metric::UniqueBlobCount
+            | Metric::UniqueTagCount
+            | Metric::ReferenceCount => "Count",
+            Metric::UniqueCommitSize | Metric::UniqueTreeSize | Metric::UniqueBlobSize => {
+                "Total size"
+            }
+            Metric::UniqueTreeEntries => "Total tree entries",
+            Metric::MaxCommitSize | Metric::MaxBlobSize => "Maximum size",
+            Metric::MaxParentCount => "Maximum parents",
+            Metric::MaxTreeEntries => "Maximum entries",
+            Metric::MaxHistoryDepth => "Maximum history depth",
+            Metric::MaxTagDepth => "Maximum tag depth",
+            Metric::MaxExpandedTreeCount => "Number of directories",
+            Metric::MaxPathDepth => "Maximum path depth",
+            Metric::MaxPathLength => "Maximum path length",
+            Metric::MaxExpandedBlobCount => "Number of files",
+            Metric::MaxExpandedBlobSize => "Total size of files",
+            Metric::MaxExpandedLinkCount => "Number of symlinks",
+            Metric::MaxExpandedSubmoduleCount => "Number of submodules",
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        match self {
+            Metric::UniqueCommitSize
+            | Metric::MaxCommitSize
+            | Metric::UniqueTreeSize
+            | Metric::UniqueBlobSize
+            | Metric::MaxBlobSize
+            | Metric::MaxPathLength
+            | Metric::MaxExpandedBlobSize => Unit::Bytes,
+            _ => Unit::Count,
+        }
+    }
+
+    /// The value at which `git-sizer` considers the metric to reach one level of concern
+    pub fn scale(&self) -> f64 {
+        match self {
+            Metric::UniqueCommitCount => 500e3,
+            Metric::UniqueCommitSize => 250e6,
+            Metric::MaxCommitSize => 50e3,
+            Metric::MaxHistoryDepth => 500e3,
+            Metric::MaxParentCount => 10.0,
+            Metric::UniqueTreeCount => 1.5e6,
+            Metric::UniqueTreeSize => 2e9,
+            Metric::UniqueTreeEntries => 50e6,
+            Metric::MaxTreeEntries => 2.5e3,
+            Metric::UniqueBlobCount => 1.5e6,
+            Metric::UniqueBlobSize => 10e9,
+            Metric::MaxBlobSize => 10e6,
+            Metric::UniqueTagCount => 25e3,
+            Metric::MaxTagDepth => 1.001,
+            Metric::ReferenceCount => 25e3,
+            Metric::MaxPathDepth => 10.0,
+            Metric::MaxPathLength => 100.0,
+            Metric::MaxExpandedTreeCount => 2000.0,
+            Metric::MaxExpandedBlobCount => 50e3,
+            Metric::MaxExpandedBlobSize => 1e9,
+            Metric::MaxExpandedLinkCount => 25e3,
+            Metric::MaxExpandedSubmoduleCount => 100.0,
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+/// The results of running `git-sizer` on a repository, as reported by `git-sizer --json`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizerReport {
+    pub unique_commit_count: u64,
+    pub unique_commit_size: u64,
+    pub max_commit_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_commit: Option<String>,
+    pub max_history_depth: u64,
+    pub max_parent_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_parent_count_commit: Option<String>,
+    pub unique_tree_count: u64,
+    pub unique_tree_size: u64,
+    pub unique_tree_entries: u64,
+    pub max_tree_entries: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tree_entries_tree: Option<String>,
+    pub unique_blob_count: u64,
+    pub unique_blob_size: u64,
+    pub max_blob_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_blob_size_blob: Option<String>,
+    pub unique_tag_count: u64,
+    pub max_tag_depth: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tag_depth_tag: Option<String>,
+    pub reference_count: u64,
+    pub reference_groups: BTreeMap<String, u64>,
+    pub max_path_depth: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_path_depth_tree: Option<String>,
+    pub max_path_length: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_path_length_tree: Option<String>,
+    pub max_expanded_tree_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expanded_tree_count_tree: Option<String>,
+    pub max_expanded_blob_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expanded_blob_count_tree: Option<String>,
+    pub max_expanded_blob_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expanded_blob_size_tree: Option<String>,
+    pub max_expanded_link_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expanded_link_count_tree: Option<String>,
+    pub max_expanded_submodule_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expanded_submodule_count_tree: Option<String>,
+}
+
+// The order and nesting of the rows in `git-sizer`'s text output
+enum Row {
+    Section(usize, &'static str),
+    Item(usize, Metric),
+    ReferenceGroups(usize),
+    Blank,
+}
+
+const TEXT_LAYOUT: [Row; 38] = [
+    Row::Section(0, "Overall repository size"),
+    Row::Section(1, "Commits"),
+    Row::Item(2, Metric::UniqueCommitCount),
+    Row::Item(2, Metric::UniqueCommitSize),
+    Row::Section(1, "Trees"),
+    Row::Item(2, Metric::UniqueTreeCount),
+    Row::Item(2, Metric::UniqueTreeSize),
+    Row::Item(2, Metric::UniqueTreeEntries),
+    Row::Section(1, "Blobs"),
+    Row::Item(2, Metric::UniqueBlobCount),
+    Row::Item(2, Metric::UniqueBlobSize),
+    Row::Section(1, "Annotated tags"),
+    Row::Item(2, Metric::UniqueTagCount),
+    Row::Section(1, "References"),
+    Row::Item(2, Metric::ReferenceCount),
+    Row::ReferenceGroups(3),
+    Row::Blank,
+    Row::Section(0, "Biggest objects"),
+    Row::Section(1, "Commits"),
+    Row::Item(2, Metric::MaxCommitSize),
+    Row::Item(2, Metric::MaxParentCount),
+    Row::Section(1, "Trees"),
+    Row::Item(2, Metric::MaxTreeEntries),
+    Row::Section(1, "Blobs"),
+    Row::Item(2, Metric::MaxBlobSize),
+    Row::Blank,
+    Row::Section(0, "History structure"),
+    Row::Item(1, Metric::MaxHistoryDepth),
+    Row::Item(1, Metric::MaxTagDepth),
+    Row::Blank,
+    Row::Section(0, "Biggest checkouts"),
+    Row::Item(1, Metric::MaxExpandedTreeCount),
+    Row::Item(1, Metric::MaxPathDepth),
+    Row::Item(1, Metric::MaxPathLength),
+    Row::Item(1, Metric::MaxExpandedBlobCount),
+    Row::Item(1, Metric::MaxExpandedBlobSize),
+    Row::Item(1, Metric::MaxExpandedLinkCount),
+    Row::Item(1, Metric::MaxExpandedSubmoduleCount),
+];
+
+// The reference groups that `git-sizer` knows about, in the order it outputs them
+const REFERENCE_GROUPS: [(&str, &str); 9] = [
+    ("branches", "Branches"),
+    ("tags", "Tags"),
+    ("remotes", "Remote-tracking refs"),
+    ("pulls", "Pull request refs"),
+    ("changes", "Changes"),
+    ("notes", "Git notes"),
+    ("stash", "Git stash"),
+    ("ignored", "Ignored"),
+    ("other", "Other"),
+];
+
+impl SizerReport {
+    pub fn from_json(json: &str) -> Result<SizerReport, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn value(&self, metric: Metric) -> u64 {
+        match metric {
+            Metric::UniqueCommitCount => self.unique_commit_count,
+            Metric::UniqueCommitSize => self.unique_commit_size,
+            Metric::MaxCommitSize => self.max_commit_size,
+            Metric::MaxHistoryDepth => self.max_history_depth,
+            Metric::MaxParentCount => self.max_parent_count,
+            Metric::UniqueTreeCount => self.unique_tree_count,
+            Metric::UniqueTreeSize => self.unique_tree_size,
+            Metric::UniqueTreeEntries => self.unique_tree_entries,
+            Metric::MaxTreeEntries => self.max_tree_entries,
+            Metric::UniqueBlobCount => self.unique_blob_count,
+            Metric::UniqueBlobSize => self.unique_blob_size,
+            Metric::MaxBlobSize => self.max_blob_size,
+            Metric::UniqueTagCount => self.unique_tag_count,
+            Metric::MaxTagDepth => self.max_tag_depth,
+            Metric::ReferenceCount => self.reference_count,
+            Metric::MaxPathDepth => self.max_path_depth,
+            Metric::MaxPathLength => self.max_path_length,
+            Metric::MaxExpandedTreeCount => self.max_expanded_tree_count,
+            Metric::MaxExpandedBlobCount => self.max_expanded_blob_count,
+            Metric::MaxExpandedBlobSize => self.max_expanded_blob_size,
+            Metric::MaxExpandedLinkCount => self.max_expanded_link_count,
+            Metric::MaxExpandedSubmoduleCount => self.max_expanded_submodule_count,
+        }
+    }
+
+    /// The object responsible for the value of a metric, e.g. the largest blob, if there is one
+    pub fn referent(&self, metric: Metric) -> Option<&str> {
+        let referent = match metric {
+            Metric::MaxCommitSize => &self.max_commit,
+            Metric::MaxParentCount => &self.max_parent_count_commit,
+            Metric::MaxTreeEntries => &self.max_tree_entries_tree,
+            Metric::MaxBlobSize => &self.max_blob_size_blob,
+            Metric::MaxTagDepth => &self.max_tag_depth_tag,
+            Metric::MaxPathDepth => &self.max_path_depth_tree,
+            Metric::MaxPathLength => &self.max_path_length_tree,
+            Metric::MaxExpandedTreeCount => &self.max_expanded_tree_count_tree,
+            Metric::MaxExpandedBlobCount => &self.max_expanded_blob_count_tree,
+            Metric::MaxExpandedBlobSize => &self.max_expanded_blob_size_tree,
+            Metric::MaxExpandedLinkCount => &self.max_expanded_link_count_tree,
+            Metric::MaxExpandedSubmoduleCount => &self.max_expanded_submodule_count_tree,
+            _ => &None,
+        };
+
+        referent.as_deref()
+    }
+
+    /// How concerning `git-sizer` considers the value of a metric to be, where 1.0 or more is worth attention
+    pub fn level_of_concern(&self, metric: Metric) -> f64 {
+        self.value(metric) as f64 / metric.scale()
+    }
+
+    /// The highest level of concern across all of the metrics in the report
+    pub fn max_level_of_concern(&self) -> f64 {
+        Metric::ALL
+            .iter()
+            .map(|metric| self.level_of_concern(*metric))
+            .fold(0.0, f64::max)
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_text(),
+            OutputFormat::Json => self.to_json(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        to_json_string(self)
+    }
+
+    /// Renders the report as a table, in the same format as `git-sizer`'s own text output
+    pub fn to_text(&self) -> String {
+        let mut output = String::new();
+        let mut footnotes: Vec<&str> = Vec::new();
+
+        output.push_str(
+            "| Name                         | Value     | Level of concern               |\n",
+        );
+        output.push_str(
+            "| ---------------------------- | --------- | ------------------------------ |\n",
+        );
+
+        for row in TEXT_LAYOUT.iter() {
+            match row {
+                Row::Section(indent, name) => {
+                    output.push_str(&format_row(*indent, name, "", "", "", ""));
+                }
+                Row::Item(indent, metric) => {
+                    let citation = match self.referent(*metric) {
+                        Some(referent) => {
+                            let index = match footnotes.iter().position(|f| *f == referent) {
+                                Some(index) => index,
+                                None => {
+                                    footnotes.push(referent);
+                                    footnotes.len() - 1
+                                }
+                            };
+                            format!("[{}]", index + 1)
+                        }
+                        None => String::new(),
+                    };
+                    let (value, unit) = format_value(self.value(*metric), metric.unit());
+
+                    output.push_str(&format_row(
+                        *indent,
+                        metric.name(),
+                        &citation,
+                        &value,
+                        &unit,
+                        &stars(self.level_of_concern(*metric)),
+                    ));
+                }
+                Row::ReferenceGroups(indent) => {
+                    for (group, count) in self.reference_groups_in_order() {
+                        let (value, unit) = format_value(count, Unit::Count);
+
+                        output.push_str(&format_row(
+                            *indent,
+                            &group,
+                            "",
+                            &value,
+                            &unit,
+                            &stars(count as f64 / Metric::ReferenceCount.scale()),
+                        ));
+                    }
+                }
+                Row::Blank => {
+                    output.push_str(&format_row(0, "", "", "", "", ""));
+                }
+            }
+        }
+
+        if !footnotes.is_empty() {
+            output.push('\n');
+
+            for (index, footnote) in footnotes.iter().enumerate() {
+                output.push_str(&format!("{:<4} {}\n", format!("[{}]", index + 1), footnote));
+            }
+        }
+
+        output
+    }
+
+    fn reference_groups_in_order(&self) -> Vec<(String, u64)> {
+        let mut groups: Vec<(String, u64)> = REFERENCE_GROUPS
+            .iter()
+            .filter_map(|(key, name)| {
+                self.reference_groups
+                    .get(*key)
+                    .map(|count| (name.to_string(), *count))
+            })
+            .collect();
+
+        for (key, count) in &self.reference_groups {
+            if !key.is_empty() && !REFERENCE_GROUPS.iter().any(|(known, _)| known == key) {
+                groups.push((key.to_string(), *count));
+            }
+        }
+
+        groups
+    }
+
+    /// Parses a report from `git-sizer`'s text output. Since the text output rounds values
+    /// to make them human-readable, the parsed values are approximate.
+    pub fn from_text(text: &str) -> Result<SizerReport, Error> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        let mut rows = text
+            .lines()
+            .filter(|line| line.starts_with('|'))
+            .skip(2)
+            .map(|line| line.split('|').map(str::trim).collect::<Vec<&str>>())
+            .peekable();
+
+        let footnotes: BTreeMap<String, String> = text
+            .lines()
+            .filter_map(|line| {
+                let (citation, referent) = line.split_once(' ')?;
+                (citation.starts_with('[') && citation.ends_with(']'))
+                    .then(|| (citation.to_string(), referent.trim().to_string()))
+            })
+            .collect();
+
+        let mut report = SizerReport::default();
+
+        for row in TEXT_LAYOUT.iter() {
+            match row {
+                Row::Section(_, _) | Row::Blank => {
+                    rows.next()
+                        .ok_or_else(|| invalid("The git-sizer output ended unexpectedly"))?;
+                }
+                Row::Item(_, metric) => {
+                    let cells = rows
+                        .next()
+                        .ok_or_else(|| invalid("The git-sizer output ended unexpectedly"))?;
+
+                    if cells.len() < 4 {
+                        return Err(invalid("The git-sizer output has an unexpected format"));
+                    }
+
+                    let name = cells[1].trim_start_matches("* ");
+                    let (name, citation) = match name.rfind('[') {
+                        Some(index) if name.ends_with(']') => {
+                            (name[..index].trim_end(), Some(&name[index..]))
+                        }
+                        _ => (name, None),
+                    };
+
+                    if name != metric.name() {
+                        return Err(invalid(&format!(
+                            "Expected `{}` in the git-sizer output, but found `{}`",
+                            metric.name(),
+                            name
+                        )));
+                    }
+
+                    let (value, referent) = report.fields_mut(*metric);
+                    *value = parse_formatted_value(cells[2])
+                        .ok_or_else(|| invalid(&format!("`{}` is not a valid value", cells[2])))?;
+
+                    if let (Some(referent), Some(citation)) = (referent, citation) {
+                        *referent = footnotes.get(citation).cloned();
+                    }
+                }
+                Row::ReferenceGroups(_) => {
+                    while let Some(cells) = rows.next_if(|cells| {
+                        cells.len() >= 4
+                            && cells[1].starts_with("* ")
+                            && !cells[2].is_empty()
+                            && !Metric::ALL
+                                .iter()
+                                .any(|metric| cells[1].trim_start_matches("* ") == metric.name())
+                    }) {
+                        let name = cells[1].trim_start_matches("* ");
+                        let key = REFERENCE_GROUPS
+                            .iter()
+                            .find(|(_, group_name)| *group_name == name)
+                            .map(|(key, _)| key.to_string())
+                            .unwrap_or_else(|| name.to_string());
+                        let count = parse_formatted_value(cells[2]).ok_or_else(|| {
+                            invalid(&format!("`{}` is not a valid value", cells[2]))
+                        })?;
+
+                        report.reference_groups.insert(key, count);
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn fields_mut(&mut self, metric: Metric) -> (&mut u64, Option<&mut Option<String>>) {
+        match metric {
+            Metric::UniqueCommitCount => (&mut self.unique_commit_count, None),
+            Metric::UniqueCommitSize => (&mut self.unique_commit_size, None),
+            Metric::MaxCommitSize => (&mut self.max_commit_size, Some(&mut self.max_commit)),
+            Metric::MaxHistoryDepth => (&mut self.max_history_depth, None),
+            Metric::MaxParentCount => (
+                &mut self.max_parent_count,
+                Some(&mut self.max_parent_count_commit),
+            ),
+            Metric::UniqueTreeCount => (&mut self.unique_tree_count, None),
+            Metric::UniqueTreeSize => (&mut self.unique_tree_size, None),
+            Metric::UniqueTreeEntries => (&mut self.unique_tree_entries, None),
+            Metric::MaxTreeEntries => (
+                &mut self.max_tree_entries,
+                Some(&mut self.max_tree_entries_tree),
+            ),
+            Metric::UniqueBlobCount => (&mut self.unique_blob_count, None),
+            Metric::UniqueBlobSize => (&mut self.unique_blob_size, None),
+            Metric::MaxBlobSize => (&mut self.max_blob_size, Some(&mut self.max_blob_size_blob)),
+            Metric::UniqueTagCount => (&mut self.unique_tag_count, None),
+            Metric::MaxTagDepth => (&mut self.max_tag_depth, Some(&mut self.max_tag_depth_tag)),
+            Metric::ReferenceCount => (&mut self.reference_count, None),
+            Metric::MaxPathDepth => (
+                &mut self.max_path_depth,
+                Some(&mut self.max_path_depth_tree),
+            ),
+            Metric::MaxPathLength => (
+                &mut self.max_path_length,
+                Some(&mut self.max_path_length_tree),
+            ),
+            Metric::MaxExpandedTreeCount => (
+                &mut self.max_expanded_tree_count,
+                Some(&mut self.max_expanded_tree_count_tree),
+            ),
+            Metric::MaxExpandedBlobCount => (
+                &mut self.max_expanded_blob_count,
+                Some(&mut self.max_expanded_blob_count_tree),
+            ),
+            Metric::MaxExpandedBlobSize => (
+                &mut self.max_expanded_blob_size,
+                Some(&mut self.max_expanded_blob_size_tree),
+            ),
+            Metric::MaxExpandedLinkCount => (
+                &mut self.max_expanded_link_count,
+                Some(&mut self.max_expanded_link_count_tree),
+            ),
+            Metric::MaxExpandedSubmoduleCount => (
+                &mut self.max_expanded_submodule_count,
+                Some(&mut self.max_expanded_submodule_count_tree),
+            ),
+        }
+    }
+}
+
+/// Serializes a value as JSON, indented in the same way as `git-sizer`'s own JSON output
+pub fn to_json_string(value: &impl Serialize) -> String {
+    let mut output = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+
+    value
+        .serialize(&mut serializer)
+        .expect("Failed to serialize to JSON");
+
+    String::from_utf8(output).expect("Serialized JSON was not valid UTF-8")
+}
+
+fn format_row(
+    indent: usize,
+    name: &str,
+    citation: &str,
+    value: &str,
+    unit: &str,
+    level_of_concern: &str,
+) -> String {
+    let prefix = if indent > 0 {
+        format!("{}* ", " ".repeat(2 * indent - 2))
+    } else {
+        String::new()
+    };
+    let length = prefix.len() + name.len() + citation.len();
+    let spacer = " ".repeat(28usize.saturating_sub(length));
+
+    format!(
+        "| {}{}{}{} | {:>5} {:<3} | {:<30} |\n",
+        prefix, name, spacer, citation, value, unit, level_of_concern
+    )
+}
+
+/// Formats a value in the same human-readable way as `git-sizer`, e.g. `1.03 KiB` or `12.5 k`
+pub fn format_value(value: u64, unit: Unit) -> (String, String) {
+    let (base, prefixes, unit_suffix): (u64, [&str; 6], &str) = match unit {
+        Unit::Count => (1000, ["", "k", "M", "G", "T", "P"], ""),
+        Unit::Bytes => (1024, ["", "Ki", "Mi", "Gi", "Ti", "Pi"], "B"),
+    };
+
+    let mut multiplier: u64 = 1;
+    let mut prefix_index = 0;
+
+    while prefix_index < prefixes.len() - 1 && value / (multiplier * base) >= 1 {
+        multiplier *= base;
+        prefix_index += 1;
+    }
+
+    if prefix_index == 0 {
+        return (value.to_string(), unit_suffix.to_string());
+    }
+
+    let whole_part = value / multiplier;
+    let mantissa = value as f64 / multiplier as f64;
+    let numeral = if whole_part >= 100 {
+        format!("{:.0}", mantissa)
+    } else if whole_part >= 10 {
+        format!("{:.1}", mantissa)
+    } else {
+        format!("{:.2}", mantissa)
+    };
+
+    (
+        numeral,
+        format!("{}{}", prefixes[prefix_index], unit_suffix),
+    )
+}
+
+/// Formats a number of bytes for use in a sentence, e.g. `1.03 KiB`
+pub fn format_bytes(value: u64) -> String {
+    let (numeral, unit) = format_value(value, Unit::Bytes);
+    format!("{} {}", numeral, unit)
+}
+
+/// Parses a value formatted by `format_value`, e.g. `1.03 KiB`, back into an approximate number
+fn parse_formatted_value(formatted: &str) -> Option<u64> {
+    let mut parts = formatted.split_whitespace();
+    let numeral: f64 = parts.next()?.parse().ok()?;
+    let multiplier: f64 = match parts.next().unwrap_or("") {
+        "" | "B" => 1.0,
+        "k" => 1e3,
+        "M" => 1e6,
+        "G" => 1e9,
+        "T" => 1e12,
+        "P" => 1e15,
+        "KiB" => 1024.0,
+        "MiB" => 1024f64.powi(2),
+        "GiB" => 1024f64.powi(3),
+        "TiB" => 1024f64.powi(4),
+        "PiB" => 1024f64.powi(5),
+        _ => return None,
+    };
+
+    Some((numeral * multiplier).round() as u64)
+}
+
+fn stars(level_of_concern: f64) -> String {
+    if level_of_concern > 30.0 {
+        "!".repeat(30)
+    } else {
+        "*".repeat(level_of_concern as usize)
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE_JSON: &str = r#"{
+    "unique_commit_count": 1,
+    "unique_commit_size": 1059,
+    "max_commit_size": 1059,
+    "max_commit": "3b4091f7aab031f87d515279e8f62ce2559076d9 (refs/heads/main)",
+    "max_history_depth": 1,
+    "max_parent_count": 0,
+    "max_parent_count_commit": "3b4091f7aab031f87d515279e8f62ce2559076d9 (refs/heads/main)",
+    "unique_tree_count": 1,
+    "unique_tree_size": 37,
+    "unique_tree_entries": 1,
+    "max_tree_entries": 1,
+    "max_tree_entries_tree": "3a46aa73a83a9f6b3e545686e0d1581a8e628ba3 (refs/heads/main^{tree})",
+    "unique_blob_count": 1,
+    "unique_blob_size": 13,
+    "max_blob_size": 13,
+    "max_blob_size_blob": "cd0875583aabe89ee197ea133980a9085d08e497 (refs/heads/main:README.md)",
+    "unique_tag_count": 0,
+    "max_tag_depth": 0,
+    "reference_count": 1,
+    "reference_groups": {
+        "": 1,
+        "branches": 1
+    },
+    "max_path_depth": 1,
+    "max_path_depth_tree": "3a46aa73a83a9f6b3e545686e0d1581a8e628ba3 (refs/heads/main^{tree})",
+    "max_path_length": 9,
+    "max_path_length_tree": "3a46aa73a83a9f6b3e545686e0d1581a8e628ba3 (refs/heads/main^{tree})",
+    "max_expanded_tree_count": 1,
+    "max_expanded_tree_count_tree": "3a46aa73a83a9f6b3e545686e0d1581a8e628ba3 (refs/heads/main^{tree})",
+    "max_expanded_blob_count": 1,
+    "max_expanded_blob_count_tree": "3a46aa73a83a9f6b3e545686e0d1581a8e628ba3 (refs/heads/main^{tree})",
+    "max_expanded_blob_size": 13,
+    "max_expanded_blob_size_tree": "3a46aa73a83a9f6b3e545686e0d1581a8e628ba3 (refs/heads/main^{tree})",
+    "max_expanded_link_count": 0,
+    "max_expanded_submodule_count": 0
+}"#;
+
+#[test]
+fn from_json_parses_git_sizer_output() {
+    let report = SizerReport::from_json(EXAMPLE_JSON).unwrap();
+
+    assert_eq!(report.unique_commit_size, 1059);
+    assert_eq!(report.value(Metric::MaxBlobSize), 13);
+    assert_eq!(
+        report.referent(Metric::MaxBlobSize),
+        Some("cd0875583aabe89ee197ea133980a9085d08e497 (refs/heads/main:README.md)")
+    );
+    assert_eq!(report.referent(Metric::MaxExpandedLinkCount), None);
+    assert_eq!(report.reference_groups.get("branches"), Some(&1));
+}
+
+#[test]
+fn from_json_errors_on_invalid_json() {
+    assert!(SizerReport::from_json("Not JSON").is_err());
+}
+
+#[test]
+fn to_json_matches_git_sizer_output() {
+    let report = SizerReport::from_json(EXAMPLE_JSON).unwrap();
+
+    assert_eq!(report.to_json(), EXAMPLE_JSON);
+}
+
+#[test]
+fn to_text_renders_git_sizer_style_table() {
+    let report = SizerReport::from_json(EXAMPLE_JSON).unwrap();
+
+    insta::assert_yaml_snapshot!(report.to_text());
+}
+
+#[test]
+fn from_text_parses_git_sizer_style_table() {
+    let report = SizerReport::from_json(EXAMPLE_JSON).unwrap();
+    let parsed = SizerReport::from_text(&report.to_text()).unwrap();
+
+    assert_eq!(parsed.unique_commit_size, 1055);
+    assert_eq!(parsed.unique_tree_size, 37);
+    assert_eq!(parsed.max_blob_size_blob, report.max_blob_size_blob);
+    assert_eq!(parsed.max_parent_count_commit, report.max_commit);
+    assert_eq!(parsed.reference_groups.get("branches"), Some(&1));
+    assert_eq!(parsed.to_text(), report.to_text());
+}
+
+#[test]
+fn from_text_errors_on_unexpected_text() {
+    assert!(SizerReport::from_text("").is_err());
+    assert!(SizerReport::from_text("error: could not clone repository").is_err());
+}
+
+#[test]
+fn level_of_concern_is_relative_to_metric_scale() {
+    let report = SizerReport {
+        max_blob_size: 25_000_000,
+        max_tree_entries: 100,
+        ..Default::default()
+    };
+
+    assert_eq!(report.level_of_concern(Metric::MaxBlobSize), 2.5);
+    assert_eq!(report.max_level_of_concern(), 2.5);
+    assert!(report
+        .to_text()
+        .contains("|   * Maximum size             |  23.8 MiB | **                             |"));
+}
+
+#[test]
+fn format_value_uses_git_sizer_prefixes() {
+    assert_eq!(
+        format_value(37, Unit::Bytes),
+        ("37".to_string(), "B".to_string())
+    );
+    assert_eq!(
+        format_value(1059, Unit::Bytes),
+        ("1.03".to_string(), "KiB".to_string())
+    );
+    assert_eq!(
+        format_value(12_500, Unit::Count),
+        ("12.5".to_string(), "k".to_string())
+    );
+    assert_eq!(
+        format_value(250_000_000, Unit::Count),
+        ("250".to_string(), "M".to_string())
+    );
+}
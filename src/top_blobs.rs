@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::history::History;
+use crate::sizer_report::format_bytes;
+
+/// A large blob, with every path it has been stored at and the commit which first introduced it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LargeBlob {
+    pub oid: String,
+    pub size: u64,
+    /// The paths the blob has been stored at, in the order they first appeared in history
+    pub paths: Vec<String>,
+    pub commit: String,
+    /// When the blob was introduced, as an ISO 8601 timestamp
+    pub date: String,
+}
+
+impl LargeBlob {
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}: {}, introduced in {} on {}",
+            self.paths.join(", "),
+            format_bytes(self.size),
+            &self.commit[..self.commit.len().min(7)],
+            &self.date[..self.date.len().min(10)]
+        )
+    }
+}
+
+/// Finds the largest blobs in a repository's history, largest first
+pub fn largest_blobs(history: &History, count: usize) -> Vec<LargeBlob> {
+    let mut blobs: HashMap<&str, LargeBlob> = HashMap::new();
+
+    for commit in &history.commits {
+        for change in &commit.changes {
+            let blob = blobs.entry(&change.blob).or_insert_with(|| LargeBlob {
+                oid: change.blob.clone(),
+                size: history.blob_size(&change.blob),
+                paths: Vec::new(),
+                commit: commit.oid.clone(),
+                date: commit.date.clone(),
+            });
+
+            if !blob.paths.contains(&change.path) {
+                blob.paths.push(change.path.clone());
+            }
+        }
+    }
+
+    let mut blobs: Vec<LargeBlob> = blobs.into_values().collect();
+    blobs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.oid.cmp(&b.oid)));
+    blobs.truncate(count);
+    blobs
+}
+
+#[test]
+fn largest_blobs_lists_paths_and_introducing_commit() {
+    let directory = tempfile::tempdir().unwrap();
+    let history = History::read(
+        &crate::history::example_repository(directory.path()),
+        &crate::ref_selection::RefSelection::default(),
+    )
+    .unwrap();
+
+    let blobs = largest_blobs(&history, 2);
+
+    assert_eq!(blobs.len(), 2);
+    assert_eq!(blobs[0].size, 5000);
+    assert_eq!(blobs[0].paths, vec!["assets/video.mp4"]);
+    assert_eq!(blobs[0].commit, history.commits[1].oid);
+    assert_eq!(blobs[1].size, 2000);
+    assert_eq!(
+        blobs[1].paths,
+        vec!["assets/logo.png", "assets/copy of logo.png"]
+    );
+    assert_eq!(blobs[1].commit, history.commits[0].oid);
+    assert_eq!(
+        blobs[1].to_text(),
+        format!(
+            "assets/logo.png, assets/copy of logo.png: 1.95 KiB, introduced in {} on 2024-01-15",
+            &history.commits[0].oid[..7]
+        )
+    );
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::io::Error;
+
+use crate::report::Report;
+
+/// The suffix which `gh repo clone` uses to clone a repository's wiki, e.g. `github/gh-sizer.wiki`
+const WIKI_SUFFIX: &str = ".wiki";
+
+/// The name with owner of a repository's wiki, which is a separate Git repository
+pub fn wiki_nwo(nwo: &str) -> String {
+    format!("{}{}", nwo, WIKI_SUFFIX)
+}
+
+/// Whether a name with owner refers to a repository's wiki rather than the repository itself
+pub fn is_wiki(nwo: &str) -> bool {
+    nwo.ends_with(WIKI_SUFFIX)
+}
+
+/// Whether cloning a repository failed because it doesn't exist. Wikis which have been enabled
+/// but have never had a page created don't exist yet, so they can't be cloned.
+pub fn is_missing_repository(error: &Error) -> bool {
+    error.to_string().to_lowercase().contains("not found")
+}
+
+/// Whether a shallow clone failed because there are no commits since the requested date. Older
+/// versions of Git report this as an error processing the shallow info.
+pub fn has_no_commits_since(error: &Error) -> bool {
+    let message = error.to_string();
+
+    message.contains("no commits selected for shallow requests")
+        || message.contains("error processing shallow info")
+}
+
+/// The size of a repository's wiki, if it has one
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Wiki {
+    /// The wiki's name with owner, e.g. `github/gh-sizer.wiki`
+    pub repository: String,
+    /// The wiki's report, or `None` if the repository doesn't have a wiki or the wiki has no
+    /// commits in the selected history
+    pub report: Option<Box<Report>>,
+    /// The date which history was selected from, if the wiki has no commits since then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_commits_since: Option<String>,
+}
+
+impl Wiki {
+    pub fn to_text(&self) -> String {
+        match (&self.report, &self.no_commits_since) {
+            (Some(report), _) => format!("{}:\n\n{}", self.repository, report.to_text()),
+            (None, Some(since)) => format!(
+                "{} has no commits since {}, so it wasn't sized\n",
+                self.repository, since
+            ),
+            (None, None) => format!(
+                "{} doesn't have a wiki\n",
+                self.repository.trim_end_matches(WIKI_SUFFIX)
+            ),
+        }
+    }
+}
+
+/// Sizes a repository's wiki with the given function, noting if the repository doesn't have one,
+/// or if history was selected from a date and the wiki has no commits since then
+pub fn size_wiki(
+    nwo: &str,
+    since: Option<&str>,
+    size_repository: impl Fn(&str) -> Result<Report, Error>,
+) -> Result<Wiki, Error> {
+    let repository = wiki_nwo(nwo);
+
+    match size_repository(&repository) {
+        Ok(report) => Ok(Wiki {
+            repository,
+            report: Some(Box::new(report)),
+            no_commits_since: None,
+        }),
+        Err(e) if is_missing_repository(&e) => {
+            eprintln!("{} doesn't have a wiki, so it won't be sized", nwo);
+
+            Ok(Wiki {
+                repository,
+                report: None,
+                no_commits_since: None,
+            })
+        }
+        Err(e) if since.is_some() && has_no_commits_since(&e) => {
+            eprintln!(
+                "{} has no commits since {}, so it won't be sized",
+                repository,
+                since.unwrap_or_default()
+            );
+
+            Ok(Wiki {
+                repository,
+                report: None,
+                no_commits_since: since.map(str::to_string),
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[test]
+fn size_wiki_sizes_the_wiki_repository() {
+    let wiki = size_wiki("github/gh-sizer", None, |nwo| {
+        assert_eq!(nwo, "github/gh-sizer.wiki");
+        Ok(Report::default())
+    })
+    .unwrap();
+
+    assert_eq!(wiki.repository, "github/gh-sizer.wiki");
+    assert_eq!(wiki.report, Some(Box::new(Report::default())));
+}
+
+#[test]
+fn size_wiki_notes_repositories_without_wikis() {
+    let wiki = size_wiki("github/gh-sizer", None, |_| {
+        Err(Error::other(
+            "remote: Repository not found.\nfatal: repository 'https://github.com/github/gh-sizer.wiki.git/' not found",
+        ))
+    })
+    .unwrap();
+
+    assert_eq!(wiki.report, None);
+    assert_eq!(wiki.to_text(), "github/gh-sizer doesn't have a wiki\n");
+
+    assert!(size_wiki("github/gh-sizer", None, |_| Err(Error::other(
+        "Permission denied"
+    )))
+    .is_err());
+}
+
+#[test]
+fn size_wiki_notes_wikis_without_commits_since_the_selected_date() {
+    let no_commits = || {
+        Err(Error::other(
+            "fatal: no commits selected for shallow requests\nfatal: the remote end hung up unexpectedly",
+        ))
+    };
+
+    let wiki = size_wiki("github/gh-sizer", Some("2024-01-01"), |_| no_commits()).unwrap();
+
+    assert_eq!(wiki.report, None);
+    assert_eq!(wiki.no_commits_since, Some("2024-01-01".to_string()));
+    assert_eq!(
+        wiki.to_text(),
+        "github/gh-sizer.wiki has no commits since 2024-01-01, so it wasn't sized\n"
+    );
+
+    assert!(size_wiki("github/gh-sizer", None, |_| no_commits()).is_err());
+}
@@ -274,3 +274,52 @@ fn repo_command_outputs_repo_size_in_json_to_stdout() -> Result<(), Box<dyn std:
 
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "windows_integration_tests")]
+fn org_command_errors_without_gh() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("org")
+        .arg("gh-sizer-sandbox")
+        .arg("--gh-command")
+        .arg("noop");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "`gh` not found. To use gh-sizer, please install the GitHub CLI (https://cli.github.com)",
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "windows_integration_tests")]
+fn repo_command_errors_with_missing_policy_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("repo")
+        .arg("gh-sizer-sandbox/first-repo")
+        .arg("--policy")
+        .arg("does-not-exist.toml");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Failed to read policy file does-not-exist.toml",
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "windows_integration_tests")]
+fn local_command_errors_when_path_is_not_a_repo() -> Result<(), Box<dyn std::error::Error>> {
+    let directory = tempfile::tempdir()?;
+    let mut cmd = Command::cargo_bin("gh-sizer")?;
+
+    cmd.arg("local").arg(directory.path());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a Git repository"));
+
+    Ok(())
+}